@@ -0,0 +1,189 @@
+/*
+ * C interface to libscroll
+ *
+ * Regenerate with `cbindgen --config cbindgen.toml --output include/libscroll.h`
+ * after changing src/ffi.rs
+ */
+
+#ifndef LIBSCROLL_H
+#define LIBSCROLL_H
+
+#include <stdint.h>
+
+#define RLSCROLL_OK 0
+
+#define RLSCROLL_ERR_NULL_HANDLE -1
+
+#define RLSCROLL_ERR_MISSING_TIMESTAMP -2
+
+#define RLSCROLL_ERR_ZERO_TIMESTAMP -3
+
+#define RLSCROLL_ERR_NON_FINITE -4
+
+#define RLSCROLL_ERR_NO_CONFIG_DIR -5
+
+#define RLSCROLL_ERR_NEGATIVE_DURATION -6
+
+#define RLSCROLL_ERR_CONFIG_UNREADABLE -7
+
+#define RLSCROLL_ERR_INVALID_CONFIG -8
+
+#define RLSCROLL_PHASE_IDLE 0
+
+#define RLSCROLL_PHASE_TRACKING 1
+
+#define RLSCROLL_PHASE_FLINGING 2
+
+#define RLSCROLL_PHASE_BOUNCING 3
+
+#define RLSCROLL_PHASE_ANIMATING 4
+
+/**
+ * Opaque handle to a scrollview, exposed to C as rlscroll_scrollview
+ *
+ * Wraps the scrollview together with the last sampled position, so that the
+ * per-axis output functions can be called one after another for a single frame
+ * without advancing the scrollview twice
+ */
+typedef struct rlscroll_scrollview rlscroll_scrollview;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Allocate a new scrollview using the global config
+ *
+ * The returned handle must be released with rlscroll_destroy_scrollview()
+ */
+rlscroll_scrollview *rlscroll_create_scrollview(void);
+
+/**
+ * Release a scrollview created with rlscroll_create_scrollview()
+ */
+void rlscroll_destroy_scrollview(rlscroll_scrollview *handle);
+
+int rlscroll_set_geometry(rlscroll_scrollview *handle,
+                          double content_height,
+                          double content_width,
+                          double viewport_height,
+                          double viewport_width);
+
+/**
+ * Returns 1 if the scrollview should keep being sampled in absence of events, 0 otherwise
+ */
+int rlscroll_animating(rlscroll_scrollview *handle);
+
+/**
+ * Re-read the global config file, see reload_config()
+ *
+ * Scrollviews pick up the new config on their next sample
+ */
+int rlscroll_reload_config(void);
+
+/**
+ * Reload the global config file if it changed since the last call, see ConfigWatcher
+ *
+ * Returns 1 if the config was reloaded, 0 if the file is unchanged, or an RLSCROLL_ERR_* code.
+ * The first call only records the current state of the file
+ */
+int rlscroll_poll_config(void);
+
+/**
+ * Set the application whose [app.<name>] config sections apply from the next reload,
+ * or clear it if name is null
+ */
+int rlscroll_set_application_name(const char *name);
+
+/**
+ * Override a value of the global config by its ini key, see set_runtime_override()
+ */
+int rlscroll_set_runtime_override(const char *key, double value);
+
+int rlscroll_set_source_undefined(rlscroll_scrollview *handle);
+
+int rlscroll_set_source_touchscreen(rlscroll_scrollview *handle);
+
+int rlscroll_set_source_touchpad(rlscroll_scrollview *handle);
+
+int rlscroll_set_source_mousewheel(rlscroll_scrollview *handle);
+
+int rlscroll_set_source_mousewheel_precise(rlscroll_scrollview *handle);
+
+int rlscroll_set_source_passthrough(rlscroll_scrollview *handle);
+
+int rlscroll_set_source_passthrough_kinetic(rlscroll_scrollview *handle);
+
+/**
+ * Set the source of a scrollview from the device match table of its config, see
+ * Scrollview::set_source_for_device()
+ */
+int rlscroll_set_source_for_device(rlscroll_scrollview *handle, const char *device);
+
+/**
+ * Force the capabilities of a scrollview regardless of its source, each flag is 0 or 1
+ */
+int rlscroll_set_capabilities(rlscroll_scrollview *handle, int overscrolls, int kinetic, int accelerates);
+
+/**
+ * Go back to the capabilities configured for the source in use
+ */
+int rlscroll_reset_capabilities(rlscroll_scrollview *handle);
+
+int rlscroll_signal_pan_x(rlscroll_scrollview *handle, double amount, uint64_t timestamp);
+
+int rlscroll_signal_pan_y(rlscroll_scrollview *handle, double amount, uint64_t timestamp);
+
+int rlscroll_signal_release(rlscroll_scrollview *handle, uint64_t timestamp);
+
+int rlscroll_signal_interrupt(rlscroll_scrollview *handle, uint64_t timestamp);
+
+/**
+ * Horizontal distance moved since the last call to this function
+ */
+double rlscroll_get_pan_x(rlscroll_scrollview *handle, uint64_t timestamp);
+
+/**
+ * Vertical distance moved since the last call to this function
+ */
+double rlscroll_get_pan_y(rlscroll_scrollview *handle, uint64_t timestamp);
+
+double rlscroll_get_pos_x(rlscroll_scrollview *handle, uint64_t timestamp);
+
+double rlscroll_get_pos_y(rlscroll_scrollview *handle, uint64_t timestamp);
+
+/**
+ * Horizontal velocity of the content in px/ms, see Scrollview::velocity()
+ */
+double rlscroll_get_velocity_x(rlscroll_scrollview *handle, uint64_t timestamp);
+
+/**
+ * Vertical velocity of the content in px/ms, see Scrollview::velocity()
+ */
+double rlscroll_get_velocity_y(rlscroll_scrollview *handle, uint64_t timestamp);
+
+/**
+ * One of the RLSCROLL_PHASE_* values for the horizontal axis, see Scrollview::phase()
+ */
+int rlscroll_get_phase_x(rlscroll_scrollview *handle);
+
+/**
+ * One of the RLSCROLL_PHASE_* values for the vertical axis, see Scrollview::phase()
+ */
+int rlscroll_get_phase_y(rlscroll_scrollview *handle);
+
+/**
+ * Move the content by the given offset immediately, see Scrollview::force_pan()
+ */
+int rlscroll_force_pan(rlscroll_scrollview *handle, double dx, double dy);
+
+/**
+ * Move the content to the given position immediately, see Scrollview::jump_to()
+ */
+int rlscroll_force_jump(rlscroll_scrollview *handle, double x, double y);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* LIBSCROLL_H */
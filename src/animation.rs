@@ -0,0 +1,123 @@
+use flo_curves::bezier::{self, Curve};
+use flo_curves::{BezierCurve, BezierCurveFactory, Coord2};
+
+type Time = f64;
+type Position = f64;
+
+/// Timing curve for programmatic scrolls started with Scrollview::scroll_to() or scroll_by()
+///
+/// Curves map the fraction of the animation duration that has elapsed (x) to the
+/// fraction of the distance that has been covered (y), like CSS timing functions
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Easing {
+    /// Constant velocity for the whole animation
+    Linear,
+    /// Starts slow and finishes at full speed, equivalent to cubic-bezier(0.42, 0, 1, 1)
+    EaseIn,
+    /// Starts at full speed and slows into the target, equivalent to cubic-bezier(0, 0, 0.58, 1)
+    EaseOut,
+    /// Slow at both ends, equivalent to cubic-bezier(0.42, 0, 0.58, 1)
+    #[default]
+    EaseInOut,
+    /// A custom curve through (0, 0), (x1, y1), (x2, y2), (1, 1), with x1 and x2 within [0, 1]
+    CubicBezier(f64, f64, f64, f64),
+}
+
+impl Easing {
+    fn curve(&self) -> Curve<Coord2> {
+        let (x1, y1, x2, y2) = match *self {
+            Easing::Linear => (0.0, 0.0, 1.0, 1.0),
+            Easing::EaseIn => (0.42, 0.0, 1.0, 1.0),
+            Easing::EaseOut => (0.0, 0.0, 0.58, 1.0),
+            Easing::EaseInOut => (0.42, 0.0, 0.58, 1.0),
+            Easing::CubicBezier(x1, y1, x2, y2) => (x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2),
+        };
+
+        Curve::from_points(Coord2(0.0, 0.0), (Coord2(x1, y1), Coord2(x2, y2)), Coord2(1.0, 1.0))
+    }
+
+    /// Finds the curve parameter at which the curve has progressed the given fraction through time
+    fn t_for(curve: &Curve<Coord2>, fraction: f64) -> f64 {
+        let (cp1, cp2) = curve.control_points();
+
+        bezier::solve_basis_for_t(0.0, cp1.0, cp2.0, 1.0, fraction)
+            .first()
+            .cloned()
+            .unwrap_or(fraction)
+    }
+
+    /// The fraction of the distance covered after the given fraction of the duration
+    pub fn progress(&self, fraction: f64) -> f64 {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let curve = self.curve();
+
+        curve.point_at_pos(Self::t_for(&curve, fraction)).1
+    }
+
+    /// Rate of change of progress with respect to the elapsed fraction of the duration
+    pub fn slope(&self, fraction: f64) -> f64 {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let curve = self.curve();
+        let t = Self::t_for(&curve, fraction);
+
+        let (cp1, cp2) = curve.control_points();
+        let (d1, d2, d3) = bezier::derivative4(curve.start_point(), cp1, cp2, curve.end_point());
+        let tangent = bezier::de_casteljau3(t, d1, d2, d3);
+
+        if tangent.0 == 0.0 {
+            // vertical tangent at the very ends of some custom curves, treat as a stop
+            0.0
+        } else {
+            tangent.1 / tangent.0
+        }
+    }
+}
+
+/// A timed move of a single axis from one position to another
+#[derive(Clone, Copy, Debug)]
+pub struct Animation {
+    start_time: Time,
+    duration: Time,
+    from: Position,
+    to: Position,
+    easing: Easing,
+}
+
+impl Animation {
+    pub fn new(start_time: Time, duration: Time, from: Position, to: Position, easing: Easing) -> Animation {
+        Animation { start_time, duration, from, to, easing }
+    }
+
+    pub fn target(&self) -> Position {
+        self.to
+    }
+
+    fn fraction_at(&self, time: Time) -> f64 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            ((time - self.start_time) / self.duration).clamp(0.0, 1.0)
+        }
+    }
+
+    pub fn finished_at(&self, time: Time) -> bool {
+        time >= self.start_time + self.duration
+    }
+
+    pub fn position_at(&self, time: Time) -> Position {
+        if self.finished_at(time) {
+            return self.to;
+        }
+
+        self.from + (self.to - self.from) * self.easing.progress(self.fraction_at(time))
+    }
+
+    /// Velocity of the animated content in px/ms
+    pub fn velocity_at(&self, time: Time) -> f64 {
+        if self.finished_at(time) {
+            return 0.0;
+        }
+
+        (self.to - self.from) / self.duration * self.easing.slope(self.fraction_at(time))
+    }
+}
@@ -0,0 +1,943 @@
+/*!
+ * Scrolling physics tunables, and loading and validating them from ini files
+ *
+ * Nothing in here touches the filesystem unless asked to. The global config used by
+ * Scrollview::new() is read once, the first time it is needed, from the location set
+ * with set_config_location(), and afterwards only when reload_config() is called or a
+ * ConfigWatcher notices a file changed. Scrollviews following the global config swap
+ * in the new one at the start of their next sample()
+ *
+ * The global config is layered, see the layers module for how the files combine
+ */
+
+use std::collections::HashMap;
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use app_dirs::*;
+use crate::layers::{Layer, LayeredConfig, Overrides};
+use crate::deceleration::{Constant, Deceleration, DecelerationModel, Exponential, PowerLaw};
+use crate::device::{DeviceId, DeviceMatch};
+use crate::spring::Spring;
+use crate::{ini, Capabilities, Error, Source};
+
+const APP_INFO: AppInfo = AppInfo { name: "libscroll", author: "Sawyer Bergeron" };
+
+/// Environment variable that overrides where the default config file is read from.
+/// Setting it to an empty string disables reading a config file at all
+pub const CONFIG_ENV: &str = "LIBSCROLL_CONFIG";
+
+/// Tunables for the scrolling physics of a scrollview
+///
+/// Each scrollview owns its own copy, so differently behaving views can coexist
+/// within a single process. The file-backed global config is only used as the
+/// default for scrollviews created through Scrollview::new()
+#[allow(non_snake_case)]
+#[derive(Clone, Debug)]
+pub struct Config {
+
+	pub EVENT_EXPIRY_COUNT: usize,
+	pub SAMPLE_EXPIRY_COUNT: usize,
+
+	pub TICKS_TO_COAST: f64,
+	pub TIMESTEP: f64,
+	pub MIN_VELOCITY_TO_IDLE: f64,
+	pub POST_ACCEL_SCALE_VELOCITY: f64,
+	pub PRE_ACCEL_SCALE_VELOCITY: f64,
+	pub SHIFT_WINDOW_MS: f64,
+	pub OVERSCROLL_ELASTICITY_COEFFICIENT: f64,
+    /// The spring overscrolled flings bounce back with, see spring()
+    pub SPRING_STIFFNESS: f64,
+    pub SPRING_DAMPING_RATIO: f64,
+    /// Deprecated, older config files describe the spring with these instead. None unless
+    /// given, see spring()
+    pub CONTENT_MASS_VALUE: Option<f64>,
+    pub OVERSCROLL_SPRING_CONSTANT: Option<f64>,
+    pub BOUNCE_DAMP_FACTOR: Option<f64>,
+	pub MAX_MS_WITHOUT_ZERO_INJECTION: f64,
+	pub MULTIPLY_FIRST_EVENT: f64,
+    pub ACCEL_DECEL_DESCRIMINANT: f64,
+    pub ACCELERATION_EXPONENT: f64,
+    pub FLING_BOOST_CONSTANT_FACTOR: f64,
+    pub PAGE_SKIP_VELOCITY: f64,
+    /// How flings slow down, parameterized by the values below, see deceleration()
+    pub DECELERATION: DecelerationModel,
+    pub FRICTION_COEFFICIENT: f64,
+    pub FRICTION_EXPONENT: f64,
+    pub DECELERATION_RATE: f64,
+    pub CONSTANT_DECELERATION: f64,
+
+	pub FLIPS_TO_IDLE: u64,
+
+    /// Capabilities of the source in use, None leaves them at the defaults for that source,
+    /// see Capabilities::of()
+    pub OVERSCROLLS: Option<bool>,
+    pub KINETIC: Option<bool>,
+    pub ACCELERATES: Option<bool>,
+
+    /// Values that differ for a specific Source, applied over the rest of the config for
+    /// scrollviews with that source set, see for_source()
+    pub profiles: HashMap<Source, Overrides>,
+
+    /// Chooses the source for devices by name, see source_for_device(). Earlier entries win
+    pub devices: Vec<DeviceMatch>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            TIMESTEP: 0.1,
+
+            MIN_VELOCITY_TO_IDLE: 0.002,
+
+            EVENT_EXPIRY_COUNT: 20,
+            SAMPLE_EXPIRY_COUNT: 20,
+
+            TICKS_TO_COAST: 1.6,
+
+            FLIPS_TO_IDLE: 20,
+
+            POST_ACCEL_SCALE_VELOCITY: 19.0,
+            PRE_ACCEL_SCALE_VELOCITY: 1.0,
+
+            SHIFT_WINDOW_MS: 0.0,
+
+            OVERSCROLL_ELASTICITY_COEFFICIENT: 1.0,
+
+            // px/ms² of pull back towards the edge for every px overscrolled
+            SPRING_STIFFNESS: 0.0005,
+            // critically damped, back to the edge as quickly as possible without passing it
+            SPRING_DAMPING_RATIO: 1.0,
+            CONTENT_MASS_VALUE: None,
+            OVERSCROLL_SPRING_CONSTANT: None,
+            BOUNCE_DAMP_FACTOR: None,
+
+            MAX_MS_WITHOUT_ZERO_INJECTION: 150.0,
+
+            MULTIPLY_FIRST_EVENT: 500.0,
+            ACCEL_DECEL_DESCRIMINANT: 10.0,
+            ACCELERATION_EXPONENT: 1.4,
+            FLING_BOOST_CONSTANT_FACTOR: 2.0,
+
+            // px/ms a paging fling has to be released at to move more than one page
+            PAGE_SKIP_VELOCITY: 4.0,
+
+            DECELERATION: DecelerationModel::PowerLaw,
+            // power law flings slow down by FRICTION_COEFFICIENT * velocity^(FRICTION_EXPONENT - 1)
+            // every ms, with velocity in units of TIMESTEP, see deceleration()
+            FRICTION_COEFFICIENT: 0.00009,
+            FRICTION_EXPONENT: 1.3,
+            // exponential flings keep this fraction of their velocity every ms
+            DECELERATION_RATE: 0.998,
+            // constant flings slow down by this many px/ms every ms
+            CONSTANT_DECELERATION: 0.002,
+
+            OVERSCROLLS: None,
+            KINETIC: None,
+            ACCELERATES: None,
+
+            profiles: HashMap::new(),
+            devices: Vec::new(),
+        }
+    }
+}
+
+/// Whether a key takes a whole number or any number
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueKind {
+    Integer,
+    Float,
+    /// true/false, yes/no, on/off or 1/0 in ini files, and 1.0/0.0 through Overrides
+    Boolean,
+    /// One of the given names in ini files, and its index in the list through Overrides
+    Choice(&'static [&'static str]),
+}
+
+impl std::fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueKind::Integer => write!(f, "a non-negative integer"),
+            ValueKind::Float => write!(f, "a number"),
+            ValueKind::Boolean => write!(f, "true or false"),
+            ValueKind::Choice(names) => write!(f, "one of {}", names.join(", ")),
+        }
+    }
+}
+
+/// The values a key accepts, with either end possibly excluded
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AllowedRange {
+    pub min: Bound<f64>,
+    pub max: Bound<f64>,
+}
+
+impl AllowedRange {
+    pub fn contains(&self, value: f64) -> bool {
+        value.is_finite() && (self.min, self.max).contains(&value)
+    }
+}
+
+impl std::fmt::Display for AllowedRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.min {
+            Bound::Included(min) => write!(f, "[{}, ", min)?,
+            Bound::Excluded(min) => write!(f, "({}, ", min)?,
+            Bound::Unbounded => write!(f, "(-inf, ")?,
+        }
+
+        match self.max {
+            Bound::Included(max) => write!(f, "{}]", max),
+            Bound::Excluded(max) => write!(f, "{})", max),
+            Bound::Unbounded => write!(f, "inf)"),
+        }
+    }
+}
+
+/// What is wrong with a config value or line
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiagnosticKind {
+    /// A line that is neither a [section] header nor a `key = value` pair
+    Malformed(String),
+    /// A section libscroll doesn't read from
+    UnknownSection(String),
+    /// A key that doesn't exist, along with the known key it was most likely meant to be
+    UnknownKey { suggestion: Option<&'static str> },
+    /// A [devices] entry naming a [device.<name>] section that isn't in the file, along with
+    /// the profile it was most likely meant to be
+    UnknownProfile { profile: String, suggestion: Option<String> },
+    /// A value that couldn't be parsed as the type the key takes
+    TypeError { expected: ValueKind, found: String },
+    /// A value outside of the range the key accepts
+    OutOfRange { value: f64, allowed: AllowedRange },
+}
+
+/// A single problem found while validating a config
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// Line of the config file the problem is on, None for configs that weren't read from a file
+    pub line: Option<usize>,
+    /// The ini key the problem is with, or the parameter for values passed in code that no key
+    /// configures directly, empty for problems with a whole line
+    pub key: String,
+    pub kind: DiagnosticKind,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+
+        match &self.kind {
+            DiagnosticKind::Malformed(line) => write!(f, "expected `key = value` or `[section]`, got `{}`", line),
+            DiagnosticKind::UnknownSection(section) => write!(f, "unknown section [{}]", section),
+            DiagnosticKind::UnknownKey { suggestion: Some(suggestion) } => {
+                write!(f, "unknown key `{}`, did you mean `{}`?", self.key, suggestion)
+            },
+            DiagnosticKind::UnknownKey { suggestion: None } => write!(f, "unknown key `{}`", self.key),
+            DiagnosticKind::UnknownProfile { profile, suggestion: Some(suggestion) } => {
+                write!(f, "`{}` refers to unknown profile `{}`, did you mean `{}`?", self.key, profile, suggestion)
+            },
+            DiagnosticKind::UnknownProfile { profile, suggestion: None } => {
+                write!(f, "`{}` refers to unknown profile `{}`", self.key, profile)
+            },
+            DiagnosticKind::TypeError { expected, found } => {
+                write!(f, "`{}` should be {}, got `{}`", self.key, expected, found)
+            },
+            DiagnosticKind::OutOfRange { value, allowed } => {
+                write!(f, "`{}` is {}, which is outside of the allowed range {}", self.key, value, allowed)
+            },
+        }
+    }
+}
+
+/// A config value that can be set from an ini file
+pub(crate) struct Key {
+    pub(crate) name: &'static str,
+    pub(crate) kind: ValueKind,
+    allowed: AllowedRange,
+    get: fn(&Config) -> f64,
+    pub(crate) set: fn(&mut Config, f64),
+}
+
+const fn at_least(min: f64) -> AllowedRange {
+    AllowedRange { min: Bound::Included(min), max: Bound::Unbounded }
+}
+
+const fn above(min: f64) -> AllowedRange {
+    AllowedRange { min: Bound::Excluded(min), max: Bound::Unbounded }
+}
+
+const fn between(min: Bound<f64>, max: Bound<f64>) -> AllowedRange {
+    AllowedRange { min, max }
+}
+
+const BOOLEAN: AllowedRange = between(Bound::Included(0.0), Bound::Included(1.0));
+
+// what the deprecated spring keys used to default to, for configs that only give some of them
+const LEGACY_CONTENT_MASS: f64 = 6000.0;
+const LEGACY_SPRING_CONSTANT: f64 = 0.4;
+const LEGACY_BOUNCE_DAMP_FACTOR: f64 = 0.9974;
+
+/// Keys that are still read from older config files, along with the key that replaces them
+static DEPRECATED: &[(&str, &str)] = &[
+    ("content_mass_value", "spring_stiffness"),
+    ("overscroll_spring_constant", "spring_stiffness"),
+    ("bounce_damping_factor", "spring_damping_ratio"),
+];
+
+const fn choice(names: &[&str]) -> AllowedRange {
+    between(Bound::Included(0.0), Bound::Included(names.len() as f64 - 1.0))
+}
+
+/// Every key read from the [config] section of an ini file
+static KEYS: &[Key] = &[
+    // each axis needs two events to interpolate between and two samples to take a velocity from
+    Key { name: "event_expiry_count", kind: ValueKind::Integer, allowed: at_least(2.0),
+        get: |c| c.EVENT_EXPIRY_COUNT as f64, set: |c, v| c.EVENT_EXPIRY_COUNT = v as usize },
+    Key { name: "sample_expiry_count", kind: ValueKind::Integer, allowed: at_least(2.0),
+        get: |c| c.SAMPLE_EXPIRY_COUNT as f64, set: |c, v| c.SAMPLE_EXPIRY_COUNT = v as usize },
+
+    Key { name: "ticks_to_coast", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.TICKS_TO_COAST, set: |c, v| c.TICKS_TO_COAST = v },
+    // the simulation steps through every frame in increments of this many ms
+    Key { name: "timestep", kind: ValueKind::Float, allowed: between(Bound::Excluded(0.0), Bound::Included(10.0)),
+        get: |c| c.TIMESTEP, set: |c, v| c.TIMESTEP = v },
+    Key { name: "min_velocity_to_idle", kind: ValueKind::Float, allowed: at_least(0.0),
+        get: |c| c.MIN_VELOCITY_TO_IDLE, set: |c, v| c.MIN_VELOCITY_TO_IDLE = v },
+    Key { name: "post_acceleration_scale_velocity", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.POST_ACCEL_SCALE_VELOCITY, set: |c, v| c.POST_ACCEL_SCALE_VELOCITY = v },
+    Key { name: "pre_acceleration_scale_velocity", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.PRE_ACCEL_SCALE_VELOCITY, set: |c, v| c.PRE_ACCEL_SCALE_VELOCITY = v },
+    Key { name: "shift_window_ms", kind: ValueKind::Float, allowed: at_least(0.0),
+        get: |c| c.SHIFT_WINDOW_MS, set: |c, v| c.SHIFT_WINDOW_MS = v },
+    Key { name: "overscroll_elasticity_coefficient", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.OVERSCROLL_ELASTICITY_COEFFICIENT, set: |c, v| c.OVERSCROLL_ELASTICITY_COEFFICIENT = v },
+    // each clears the deprecated values it replaces, see DEPRECATED
+    Key { name: "spring_stiffness", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.SPRING_STIFFNESS, set: |c, v| {
+            c.SPRING_STIFFNESS = v;
+            c.CONTENT_MASS_VALUE = None;
+            c.OVERSCROLL_SPRING_CONSTANT = None;
+        } },
+    // below 1 bounces swing back past the edge, 0 would never settle
+    Key { name: "spring_damping_ratio", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.SPRING_DAMPING_RATIO, set: |c, v| {
+            c.SPRING_DAMPING_RATIO = v;
+            c.BOUNCE_DAMP_FACTOR = None;
+        } },
+    Key { name: "content_mass_value", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.CONTENT_MASS_VALUE.unwrap_or(LEGACY_CONTENT_MASS), set: |c, v| c.CONTENT_MASS_VALUE = Some(v) },
+    Key { name: "overscroll_spring_constant", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.OVERSCROLL_SPRING_CONSTANT.unwrap_or(LEGACY_SPRING_CONSTANT), set: |c, v| c.OVERSCROLL_SPRING_CONSTANT = Some(v) },
+    // applied once every TIMESTEP, 1 would never settle
+    Key { name: "bounce_damping_factor", kind: ValueKind::Float, allowed: between(Bound::Excluded(0.0), Bound::Excluded(1.0)),
+        get: |c| c.BOUNCE_DAMP_FACTOR.unwrap_or(LEGACY_BOUNCE_DAMP_FACTOR), set: |c, v| c.BOUNCE_DAMP_FACTOR = Some(v) },
+    Key { name: "zero_delta_injection_wait_ms", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.MAX_MS_WITHOUT_ZERO_INJECTION, set: |c, v| c.MAX_MS_WITHOUT_ZERO_INJECTION = v },
+    Key { name: "first_event_multiplier", kind: ValueKind::Float, allowed: at_least(0.0),
+        get: |c| c.MULTIPLY_FIRST_EVENT, set: |c, v| c.MULTIPLY_FIRST_EVENT = v },
+    Key { name: "accel_decel_descriminant", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.ACCEL_DECEL_DESCRIMINANT, set: |c, v| c.ACCEL_DECEL_DESCRIMINANT = v },
+    Key { name: "acceleration_exponent", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.ACCELERATION_EXPONENT, set: |c, v| c.ACCELERATION_EXPONENT = v },
+    Key { name: "fling_boost_constant_factor", kind: ValueKind::Float, allowed: at_least(0.0),
+        get: |c| c.FLING_BOOST_CONSTANT_FACTOR, set: |c, v| c.FLING_BOOST_CONSTANT_FACTOR = v },
+    Key { name: "page_skip_velocity", kind: ValueKind::Float, allowed: at_least(0.0),
+        get: |c| c.PAGE_SKIP_VELOCITY, set: |c, v| c.PAGE_SKIP_VELOCITY = v },
+    Key { name: "deceleration", kind: ValueKind::Choice(DecelerationModel::NAMES), allowed: choice(DecelerationModel::NAMES),
+        get: |c| c.DECELERATION.index() as f64, set: |c, v| c.DECELERATION = DecelerationModel::from_index(v as usize) },
+    Key { name: "friction_coefficient", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.FRICTION_COEFFICIENT, set: |c, v| c.FRICTION_COEFFICIENT = v },
+    // above 2 a fling takes longer to stop the faster it was released
+    Key { name: "friction_exponent", kind: ValueKind::Float, allowed: between(Bound::Included(1.0), Bound::Included(2.0)),
+        get: |c| c.FRICTION_EXPONENT, set: |c, v| c.FRICTION_EXPONENT = v },
+    Key { name: "deceleration_rate", kind: ValueKind::Float, allowed: between(Bound::Excluded(0.0), Bound::Excluded(1.0)),
+        get: |c| c.DECELERATION_RATE, set: |c, v| c.DECELERATION_RATE = v },
+    Key { name: "constant_deceleration", kind: ValueKind::Float, allowed: above(0.0),
+        get: |c| c.CONSTANT_DECELERATION, set: |c, v| c.CONSTANT_DECELERATION = v },
+
+    Key { name: "flips_until_idle", kind: ValueKind::Integer, allowed: at_least(1.0),
+        get: |c| c.FLIPS_TO_IDLE as f64, set: |c, v| c.FLIPS_TO_IDLE = v as u64 },
+
+    // capabilities, usually set in a source section since each source has its own defaults
+    Key { name: "overscrolls", kind: ValueKind::Boolean, allowed: BOOLEAN,
+        get: |c| c.OVERSCROLLS.map_or(0.0, |v| v as u8 as f64), set: |c, v| c.OVERSCROLLS = Some(v != 0.0) },
+    Key { name: "kinetic", kind: ValueKind::Boolean, allowed: BOOLEAN,
+        get: |c| c.KINETIC.map_or(0.0, |v| v as u8 as f64), set: |c, v| c.KINETIC = Some(v != 0.0) },
+    Key { name: "accelerates", kind: ValueKind::Boolean, allowed: BOOLEAN,
+        get: |c| c.ACCELERATES.map_or(0.0, |v| v as u8 as f64), set: |c, v| c.ACCELERATES = Some(v != 0.0) },
+];
+
+impl Key {
+    pub(crate) fn find(name: &str) -> Option<&'static Key> {
+        KEYS.iter().find(|key| key.name == name)
+    }
+
+    /// The key that replaces a deprecated one, None for keys that aren't deprecated
+    pub(crate) fn replacement(name: &str) -> Option<&'static str> {
+        DEPRECATED.iter().find(|(deprecated, _)| *deprecated == name).map(|&(_, replacement)| replacement)
+    }
+
+    /// The known key closest to a misspelled one, if any is close enough to be a likely typo
+    pub(crate) fn suggest(name: &str) -> Option<&'static str> {
+        KEYS.iter()
+            .map(|key| (edit_distance(name, key.name), key.name))
+            .filter(|&(distance, _)| distance <= 3)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, name)| name)
+    }
+
+    fn parse(&self, value: &str) -> Result<f64, DiagnosticKind> {
+        let parsed = match self.kind {
+            ValueKind::Integer => value.parse::<u64>().map(|v| v as f64).ok(),
+            ValueKind::Float => value.parse::<f64>().ok(),
+            ValueKind::Boolean => match value.to_ascii_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Some(1.0),
+                "false" | "no" | "off" | "0" => Some(0.0),
+                _ => None,
+            },
+            ValueKind::Choice(names) => names.iter().position(|name| name.eq_ignore_ascii_case(value)).map(|i| i as f64),
+        };
+
+        let parsed = parsed.ok_or_else(|| DiagnosticKind::TypeError { expected: self.kind, found: value.to_owned() })?;
+        self.check(parsed)?;
+
+        Ok(parsed)
+    }
+
+    pub(crate) fn check(&self, value: f64) -> Result<(), DiagnosticKind> {
+        if self.allowed.contains(value) {
+            Ok(())
+        } else {
+            Err(DiagnosticKind::OutOfRange { value, allowed: self.allowed })
+        }
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Names of the ini sections holding the profile for each source
+pub(crate) static PROFILE_SECTIONS: &[(&str, Source)] = &[
+    ("undefined", Source::Undefined),
+    ("touchscreen", Source::Touchscreen),
+    ("touchpad", Source::Touchpad),
+    ("mousewheel", Source::Mousewheel),
+    ("mousewheel_precise", Source::PreciseMousewheel),
+    ("passthrough", Source::Passthrough),
+    ("passthrough_kinetic", Source::KineticPassthrough),
+];
+
+/// The source whose profile an ini section holds, for sections such as [touchpad] or
+/// [device.trackpoint]
+fn profile_of(section: &str) -> Option<Source> {
+    match section.strip_prefix("device.") {
+        Some(device) if !device.is_empty() => Some(Source::Device(DeviceId::named(device))),
+        _ => PROFILE_SECTIONS.iter().find(|(name, _)| *name == section).map(|&(_, source)| source),
+    }
+}
+
+/// The source a [devices] entry refers to, either a built in one such as touchpad or the
+/// name of a device profile
+fn source_named(name: &str) -> Source {
+    profile_of(name).unwrap_or_else(|| Source::Device(DeviceId::named(name)))
+}
+
+/// The profile a [devices] entry naming a missing section was most likely meant to be, out of
+/// the built in ones and the [device.<name>] sections of the file
+fn suggest_profile(name: &str, sections: &[String]) -> Option<String> {
+    PROFILE_SECTIONS.iter()
+        .map(|(section, _)| *section)
+        .chain(sections.iter().map(String::as_str).filter(|section| section.starts_with("device.")))
+        .map(|section| (edit_distance(name, section), section))
+        .filter(|&(distance, _)| distance <= 3)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, section)| section.to_owned())
+}
+
+/// Splits [app.<name>.<source>] into the application name and the source, or gives back
+/// [app.<name>] as is
+fn split_application(section: &str) -> (&str, Option<Source>) {
+    section.match_indices('.')
+        .find_map(|(split, _)| profile_of(&section[split + 1..]).map(|profile| (&section[..split], Some(profile))))
+        .unwrap_or((section, None))
+}
+
+/// Where the entries of the current section of an ini file go
+enum Section<'a> {
+    /// Config values, specific to a source if it is given
+    Values(&'a mut Overrides, Option<Source>),
+    /// The device match table
+    Devices(&'a mut Overrides),
+}
+
+/// The values set by an ini file, split by the layer they belong to
+#[derive(Debug, Default)]
+pub(crate) struct IniValues {
+    /// The [config] section, along with the per source sections such as [touchpad] and the
+    /// [devices] table
+    pub(crate) base: Overrides,
+    /// The [app.<name>] and [app.<name>.<source>] sections for the application asked for
+    pub(crate) application: Overrides,
+}
+
+/// Parse the contents of an ini file, validating every value including those in the
+/// [app.<name>] sections of other applications
+pub(crate) fn parse_ini(contents: &str, application: Option<&str>) -> Result<IniValues, Error> {
+    let mut values = IniValues::default();
+    let mut ignored = Overrides::new();
+    let mut diagnostics = Vec::new();
+    let mut section = None;
+    // [device.<name>] sections named by [devices] entries, checked once every section is known
+    let mut sections = Vec::new();
+    let mut references = Vec::new();
+
+    for (line, parsed) in ini::lines(contents) {
+        let diagnostic = |key: &str, kind| Diagnostic { line: Some(line), key: key.to_owned(), kind };
+
+        match parsed {
+            ini::Line::Section(name) => {
+                sections.push(name.to_owned());
+                section = match name {
+                    "config" => Some(Section::Values(&mut values.base, None)),
+                    "devices" => Some(Section::Devices(&mut values.base)),
+                    _ if profile_of(name).is_some() => Some(Section::Values(&mut values.base, profile_of(name))),
+                    _ if name.starts_with("app.") && name.len() > 4 => match split_application(&name[4..]) {
+                        (app, profile) if Some(app) == application => Some(Section::Values(&mut values.application, profile)),
+                        _ => Some(Section::Values(&mut ignored, None)),
+                    },
+                    _ => {
+                        // its keys are still checked, but the section itself is only reported once
+                        diagnostics.push(diagnostic("", DiagnosticKind::UnknownSection(name.to_owned())));
+                        Some(Section::Values(&mut ignored, None))
+                    },
+                };
+            },
+            ini::Line::Malformed(content) => {
+                diagnostics.push(diagnostic("", DiagnosticKind::Malformed(content.to_owned())));
+            },
+            ini::Line::Entry(name, value) => match section.as_mut() {
+                None => diagnostics.push(diagnostic(name, DiagnosticKind::UnknownSection(String::new()))),
+                Some(Section::Devices(_)) if name.is_empty() || value.is_empty() => {
+                    diagnostics.push(diagnostic(name, DiagnosticKind::Malformed(format!("{} = {}", name, value))));
+                },
+                // entries of the match table are `device name pattern = profile`
+                Some(Section::Devices(overrides)) => {
+                    if value.starts_with("device.") {
+                        references.push((line, name.to_owned(), value.to_owned()));
+                    }
+                    overrides.match_device(name, source_named(value));
+                },
+                Some(Section::Values(overrides, profile)) => match Key::find(name) {
+                    None => diagnostics.push(diagnostic(name, DiagnosticKind::UnknownKey { suggestion: Key::suggest(name) })),
+                    Some(key) => match key.parse(value) {
+                        Ok(value) => overrides.insert(*profile, key, value),
+                        Err(kind) => diagnostics.push(diagnostic(name, kind)),
+                    },
+                },
+            },
+        }
+    }
+
+    for (line, name, profile) in references {
+        if !sections.contains(&profile) {
+            let suggestion = suggest_profile(&profile, &sections);
+            diagnostics.push(Diagnostic { line: Some(line), key: name, kind: DiagnosticKind::UnknownProfile { profile, suggestion } });
+        }
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+
+    if diagnostics.is_empty() {
+        Ok(values)
+    } else {
+        Err(Error::InvalidConfig(diagnostics))
+    }
+}
+
+fn read_ini(path: &Path, application: Option<&str>) -> Result<IniValues, Error> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| Error::ConfigUnreadable(path.to_path_buf(), e.to_string()))?;
+
+    parse_ini(&contents, application)
+}
+
+impl Config {
+    /// Parse a config from the contents of an ini file
+    ///
+    /// Keys are read from the [config] section, anything not given keeps its default value.
+    /// Sections named after a source, such as [touchpad] or [mousewheel_precise], become the
+    /// profile for that source, and [device.<name>] sections device profiles. The [devices]
+    /// section holds `device name pattern = profile name` entries for source_for_device().
+    /// [app.<name>] sections are checked but not applied, see set_application_name().
+    /// If anything in the file is wrong the config is refused as a whole, with
+    /// Error::InvalidConfig listing every problem found
+    pub fn from_ini_str(contents: &str) -> Result<Config, Error> {
+        let base = parse_ini(contents, None)?.base;
+
+        let mut config = Config::default();
+        base.apply_to(&mut config);
+        base.apply_profiles_to(&mut config);
+
+        Ok(config)
+    }
+
+    /// Read and parse the ini file at the given path, see from_ini_str()
+    pub fn load_from_path<P: AsRef<Path>>(path: P) -> Result<Config, Error> {
+        let base = read_ini(path.as_ref(), None)?.base;
+
+        let mut config = Config::default();
+        base.apply_to(&mut config);
+        base.apply_profiles_to(&mut config);
+
+        Ok(config)
+    }
+
+    /// This config with the profile for the given source applied over it
+    pub fn for_source(&self, source: Source) -> Config {
+        let mut config = self.clone();
+        if let Some(profile) = self.profiles.get(&source) {
+            profile.apply_to(&mut config);
+        }
+
+        config
+    }
+
+    /// The Deceleration flings slow down by, as chosen by DECELERATION
+    pub fn deceleration(&self) -> Arc<dyn Deceleration> {
+        match self.DECELERATION {
+            DecelerationModel::PowerLaw => {
+                // FRICTION_COEFFICIENT is per unit of TIMESTEP velocity, which is TIMESTEP / 2 px/ms
+                let coefficient = self.FRICTION_COEFFICIENT * (self.TIMESTEP / 2.0).powf(self.FRICTION_EXPONENT - 2.0);
+                Arc::new(PowerLaw { coefficient, exponent: self.FRICTION_EXPONENT })
+            },
+            DecelerationModel::Exponential => Arc::new(Exponential { rate: self.DECELERATION_RATE }),
+            DecelerationModel::Constant => Arc::new(Constant { deceleration: self.CONSTANT_DECELERATION }),
+        }
+    }
+
+    /// The Spring overscrolled flings bounce back with, from SPRING_STIFFNESS and
+    /// SPRING_DAMPING_RATIO
+    ///
+    /// Any of the deprecated CONTENT_MASS_VALUE, OVERSCROLL_SPRING_CONSTANT or
+    /// BOUNCE_DAMP_FACTOR that is set takes the place of the value it replaces, giving the
+    /// spring that older versions stepped through every TIMESTEP with them
+    pub fn spring(&self) -> Spring {
+        let stiffness = if self.CONTENT_MASS_VALUE.is_some() || self.OVERSCROLL_SPRING_CONSTANT.is_some() {
+            let constant = self.OVERSCROLL_SPRING_CONSTANT.unwrap_or(LEGACY_SPRING_CONSTANT);
+            let mass = self.CONTENT_MASS_VALUE.unwrap_or(LEGACY_CONTENT_MASS);
+            2.0 * constant / (mass * self.TIMESTEP)
+        } else {
+            self.SPRING_STIFFNESS
+        };
+
+        let damping_ratio = match self.BOUNCE_DAMP_FACTOR {
+            Some(factor) => -factor.ln() / self.TIMESTEP / (2.0 * stiffness.sqrt()),
+            None => self.SPRING_DAMPING_RATIO,
+        };
+
+        Spring { stiffness, damping_ratio }
+    }
+
+    /// Add a named device profile with the given capabilities and config values, returning
+    /// the source to select it with. If the device already has a profile these are set over
+    /// it, keeping any values not given again, like Overrides::register_device()
+    pub fn register_device(&mut self, name: &str, capabilities: Capabilities, profile: &Overrides) -> Source {
+        let source = Source::Device(DeviceId::named(name));
+        let mut profile = profile.clone();
+        profile.set_capabilities(capabilities);
+        self.profiles.entry(source).or_default().merge(&profile);
+
+        source
+    }
+
+    /// The source chosen by the device match table for a device with the given name, if any
+    pub fn source_for_device(&self, device: &str) -> Option<Source> {
+        self.devices.iter().find(|entry| entry.matches(device)).map(|entry| entry.source)
+    }
+
+    /// Check every value against the range it accepts, returning a diagnostic for each one
+    /// that is out of range. Configs loaded from ini files have already been checked
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        KEYS.iter()
+            .filter_map(|key| key.check((key.get)(self)).err().map(|kind| Diagnostic {
+                line: None,
+                key: key.name.to_owned(),
+                kind,
+            }))
+            .collect()
+    }
+
+    /// Ok if every value is within range, otherwise Error::InvalidConfig, see diagnostics()
+    pub fn validate(&self) -> Result<(), Error> {
+        let diagnostics = self.diagnostics();
+
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidConfig(diagnostics))
+        }
+    }
+}
+
+/// Where the global config files are read from
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ConfigLocation {
+    /// config.ini in the system config directory (/etc/xdg/libscroll on Linux) as the system
+    /// layer, and in the user config directory as the user layer. $LIBSCROLL_CONFIG replaces
+    /// the user file when set, and disables both files when set but empty. Missing files are
+    /// not an error here, the layer is just empty
+    #[default]
+    Default,
+    /// A specific file as the user layer, which has to exist. No system file is read
+    Path(PathBuf),
+    /// Never read a config file, leaving only the defaults and runtime overrides
+    Disabled,
+}
+
+impl ConfigLocation {
+    /// The files to read, each with the layer it is for and whether it has to exist
+    fn files(&self) -> Result<Vec<(Layer, PathBuf, bool)>, Error> {
+        let in_dir = |data_type| get_app_root(data_type, &APP_INFO).map(|dir| dir.join("config.ini"));
+
+        match self {
+            ConfigLocation::Default => {
+                let user = match std::env::var_os(CONFIG_ENV) {
+                    // set but empty, as good as ConfigLocation::Disabled
+                    Some(path) if path.is_empty() => return Ok(Vec::new()),
+                    Some(path) => (Layer::User, PathBuf::from(path), true),
+                    None => (Layer::User, in_dir(AppDataType::UserConfig).map_err(|_| Error::NoConfigDir)?, false),
+                };
+                let system = in_dir(AppDataType::SharedConfig).ok().map(|path| (Layer::System, path, false));
+
+                Ok(system.into_iter().chain(Some(user)).collect())
+            },
+            ConfigLocation::Path(path) => Ok(vec![(Layer::User, path.clone(), true)]),
+            ConfigLocation::Disabled => Ok(Vec::new()),
+        }
+    }
+
+    /// Read the system, user and application layers
+    fn load(&self, application: Option<&str>) -> Result<LayeredConfig, Error> {
+        let mut layers = LayeredConfig::new();
+
+        for (layer, path, required) in self.files()? {
+            if !required && !path.is_file() {
+                continue;
+            }
+
+            let values = read_ini(&path, application)?;
+            layers.set_layer(layer, values.base);
+            // the user's section for the application wins over the system's one
+            layers.layer_mut(Layer::Application).merge(&values.application);
+        }
+
+        Ok(layers)
+    }
+}
+
+struct GlobalConfig {
+    layers: LayeredConfig,
+    config: Config,
+    location: ConfigLocation,
+    application: Option<String>,
+    loaded: bool,
+    // why the last reload failed, cleared by the next one that succeeds
+    load_error: Option<Error>,
+}
+
+impl GlobalConfig {
+    /// Replace the effective config with a freshly resolved one, and let scrollviews know
+    fn resolve(&mut self) {
+        self.config = self.layers.resolve();
+        GENERATION.fetch_add(1, Ordering::Release);
+    }
+}
+
+lazy_static! {
+    static ref CONFIG: RwLock<GlobalConfig> = RwLock::new(GlobalConfig {
+        layers: LayeredConfig::new(),
+        config: Config::default(),
+        location: ConfigLocation::Default,
+        application: None,
+        loaded: false,
+        load_error: None,
+    });
+}
+
+// bumped every time the global config is replaced, so that scrollviews following it can
+// notice without taking the lock on every frame
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn global() -> std::sync::RwLockWriteGuard<'static, GlobalConfig> {
+    CONFIG.write().expect("Couldn't lock config struct")
+}
+
+/// Set where the global config is read from on the next call to reload_config()
+///
+/// If the global config hasn't been read yet, the new location is also used for the first
+/// Scrollview::new()
+pub fn set_config_location(location: ConfigLocation) {
+    global().location = location;
+}
+
+/// Set the name of the application, whose [app.<name>] sections in the config files are
+/// applied over the [config] sections on the next call to reload_config()
+pub fn set_application_name(name: Option<&str>) {
+    global().application = name.map(str::to_owned);
+}
+
+/// Override a value of the global config by its ini key, taking precedence over every
+/// config file. Applies immediately, and is kept across reloads
+pub fn set_runtime_override(key: &str, value: f64) -> Result<(), Error> {
+    let mut global = global();
+    global.layers.layer_mut(Layer::Runtime).set(key, value)?;
+    global.resolve();
+
+    Ok(())
+}
+
+/// Remove a value set with set_runtime_override(), letting the config files decide it again
+pub fn clear_runtime_override(key: &str) {
+    let mut global = global();
+    if global.layers.layer_mut(Layer::Runtime).remove(key).is_some() {
+        global.resolve();
+    }
+}
+
+/// Add a named device profile to the runtime layer of the global config, returning the source
+/// to select it with, see Config::register_device()
+pub fn register_device(name: &str, capabilities: Capabilities, profile: &Overrides) -> Source {
+    let mut global = global();
+    let source = global.layers.layer_mut(Layer::Runtime).register_device(name, capabilities, profile);
+    global.resolve();
+
+    source
+}
+
+/// Which layer the current value of the given ini key in the global config comes from,
+/// or None if there is no such key
+pub fn config_source(key: &str) -> Option<Layer> {
+    CONFIG.read().expect("Couldn't lock config struct").layers.source_of(key)
+}
+
+/// Like config_source(), but for the value in effect while the given source is in use
+pub fn config_source_for(source: Source, key: &str) -> Option<Layer> {
+    CONFIG.read().expect("Couldn't lock config struct").layers.source_for(source, key)
+}
+
+/// Re-read the global config files from their location
+///
+/// Scrollviews following the global config pick up the result on their next sample(),
+/// see Scrollview::follow_global_config(). On error the global config is left as it was,
+/// and the error is kept for last_load_error()
+pub fn reload_config() -> Result<(), Error> {
+    let mut global = global();
+    global.loaded = true;
+
+    let mut layers = match global.location.load(global.application.as_deref()) {
+        Ok(layers) => layers,
+        Err(e) => {
+            global.load_error = Some(e.clone());
+            return Err(e);
+        }
+    };
+    layers.set_layer(Layer::Runtime, global.layers.layer(Layer::Runtime));
+    global.layers = layers;
+    global.load_error = None;
+    global.resolve();
+
+    Ok(())
+}
+
+/// Why the global config files couldn't be loaded, or None if they were the last time they
+/// were read. Mostly of use after Scrollview::new(), which loads them the first time without
+/// returning an error, and falls back to the defaults if they can't be
+pub fn last_load_error() -> Option<Error> {
+    CONFIG.read().expect("Couldn't lock config struct").load_error.clone()
+}
+
+/// Changes every time the global config is replaced
+pub(crate) fn global_generation() -> u64 {
+    GENERATION.load(Ordering::Acquire)
+}
+
+/// The global config along with its generation, read from its location the first time this
+/// is called
+pub(crate) fn global_config() -> (Config, u64) {
+    {
+        let global = CONFIG.read().expect("Couldn't lock config struct");
+        if global.loaded {
+            return (global.config.clone(), global_generation());
+        }
+    }
+
+    // kept for last_load_error(), the defaults are used until the files can be loaded
+    let _ = reload_config();
+
+    let global = CONFIG.read().expect("Couldn't lock config struct");
+    (global.config.clone(), global_generation())
+}
+
+/// Polls the global config files for changes, reloading them when the modification time or
+/// size of any of them changes
+///
+/// Meant to be polled from the render loop or a timer while tuning, so that edits to the
+/// config files apply to running scrollviews without restarting the application. The
+/// location is looked up on every poll, so it follows set_config_location()
+#[derive(Debug, Default)]
+pub struct ConfigWatcher {
+    // modification time and length of each file as of the last poll, None if it didn't exist
+    last_seen: Vec<Option<(SystemTime, u64)>>,
+}
+
+impl ConfigWatcher {
+    /// Create a watcher that only reloads on changes made after this point
+    pub fn new() -> ConfigWatcher {
+        ConfigWatcher { last_seen: Self::stat() }
+    }
+
+    fn stat() -> Vec<Option<(SystemTime, u64)>> {
+        let location = CONFIG.read().expect("Couldn't lock config struct").location.clone();
+        let files = location.files().unwrap_or_default();
+
+        files.iter()
+            .map(|(_, path, _)| {
+                let metadata = std::fs::metadata(path).ok()?;
+                Some((metadata.modified().ok()?, metadata.len()))
+            })
+            .collect()
+    }
+
+    /// Check whether any config file changed since the last poll, and reload the global config
+    /// if it did. Returns whether a reload happened
+    ///
+    /// Files that fail to load are not retried until they change again
+    pub fn poll(&mut self) -> Result<bool, Error> {
+        let seen = Self::stat();
+        if seen == self.last_seen {
+            return Ok(false);
+        }
+
+        self.last_seen = seen;
+        reload_config()?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,238 @@
+/*!
+ * Models of how a fling slows down once released
+ *
+ * Every model works in px/ms and ms, and is solved exactly rather than stepped, so a fling
+ * travels the same distance however often it is sampled and whatever TIMESTEP is set to
+ */
+
+type Velocity = f64; // px/ms
+type TimeDelta = f64; // ms
+type Position = f64; // px
+
+// Halvings (and doublings) used by time_to_travel(), enough to pin a time down to well under
+// a microsecond over any realistic fling
+const TIME_SEARCH_ITERATIONS: usize = 64;
+
+// Below this speed (px/ms) a fling is considered stopped, for models that would otherwise
+// only approach rest without reaching it
+const REST_VELOCITY: Velocity = 0.001;
+
+/// How a fling loses speed while coasting, see Config::deceleration()
+///
+/// All functions take and give signed values, with the sign only giving the direction
+pub trait Deceleration: Send + Sync {
+    /// Velocity `elapsed` ms after coasting from `velocity`, exactly 0 once it has stopped
+    fn velocity_after(&self, velocity: Velocity, elapsed: TimeDelta) -> Velocity;
+
+    /// Distance covered `elapsed` ms after coasting from `velocity`
+    fn displacement_after(&self, velocity: Velocity, elapsed: TimeDelta) -> Position;
+
+    /// Distance covered coasting from `velocity` until coming to rest
+    fn distance(&self, velocity: Velocity) -> Position;
+
+    /// Velocity a fling has to be released at to coast exactly `distance`, the inverse of
+    /// distance()
+    fn velocity_for_distance(&self, distance: Position) -> Velocity;
+
+    /// How long coasting from `velocity` takes to cover `distance`, or None if it comes to rest
+    /// before getting that far
+    ///
+    /// Found by bisection over displacement_after() with a fixed number of iterations, models
+    /// that can be solved for time directly may want to override it
+    fn time_to_travel(&self, velocity: Velocity, distance: Position) -> Option<TimeDelta> {
+        let distance = distance.abs();
+        let displacement = |elapsed| self.displacement_after(velocity, elapsed).abs();
+
+        if distance > self.distance(velocity).abs() {
+            return None;
+        }
+
+        let mut upper = 1.0;
+        for _ in 0..TIME_SEARCH_ITERATIONS {
+            if displacement(upper) >= distance {
+                break;
+            }
+            upper *= 2.0;
+        }
+
+        let mut lower = 0.0;
+        for _ in 0..TIME_SEARCH_ITERATIONS {
+            let middle = (lower + upper) / 2.0;
+            if displacement(middle) < distance {
+                lower = middle;
+            } else {
+                upper = middle;
+            }
+        }
+
+        Some(upper)
+    }
+}
+
+/// Which Deceleration a Config uses, each parameterized by its own config values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecelerationModel {
+    /// PowerLaw with FRICTION_COEFFICIENT and FRICTION_EXPONENT
+    PowerLaw,
+    /// Exponential with DECELERATION_RATE
+    Exponential,
+    /// Constant with CONSTANT_DECELERATION
+    Constant,
+}
+
+impl DecelerationModel {
+    /// Names of the models as given for the deceleration key of ini files, in order
+    pub(crate) const NAMES: &'static [&'static str] = &["power_law", "exponential", "constant"];
+
+    pub(crate) fn index(self) -> usize {
+        match self {
+            DecelerationModel::PowerLaw => 0,
+            DecelerationModel::Exponential => 1,
+            DecelerationModel::Constant => 2,
+        }
+    }
+
+    pub(crate) fn from_index(index: usize) -> DecelerationModel {
+        match index {
+            1 => DecelerationModel::Exponential,
+            2 => DecelerationModel::Constant,
+            _ => DecelerationModel::PowerLaw,
+        }
+    }
+}
+
+/// Slows down by `coefficient * |velocity|^(exponent - 1)` px/ms every ms, so fast flings
+/// lose speed quicker than slow ones. Exponents run from 1 (constant friction) to 2
+/// (exponential decay)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PowerLaw {
+    pub coefficient: f64,
+    pub exponent: f64,
+}
+
+/// Keeps `rate` of its velocity every ms, like the deceleration rate of a UIScrollView
+/// (0.998 for normal, 0.99 for fast). Stops once under 0.001 px/ms
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Exponential {
+    pub rate: f64,
+}
+
+/// Slows down by the same `deceleration` px/ms every ms whatever the velocity
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Constant {
+    pub deceleration: f64,
+}
+
+impl PowerLaw {
+    /// At an exponent of 2 the power law is exponential decay, which never comes to a stop
+    /// on its own
+    fn as_exponential(&self) -> Option<Exponential> {
+        if self.exponent >= 2.0 {
+            Some(Exponential { rate: (-self.coefficient).exp() })
+        } else {
+            None
+        }
+    }
+}
+
+impl Deceleration for PowerLaw {
+    fn velocity_after(&self, velocity: Velocity, elapsed: TimeDelta) -> Velocity {
+        if let Some(exponential) = self.as_exponential() {
+            return exponential.velocity_after(velocity, elapsed);
+        }
+
+        // dv/dt = -k * v^a, which solves to v^(1 - a) falling linearly until it reaches 0
+        let power = 2.0 - self.exponent;
+        let remaining = (velocity.abs().powf(power) - power * self.coefficient * elapsed).max(0.0).powf(1.0 / power);
+
+        remaining.copysign(velocity)
+    }
+
+    fn displacement_after(&self, velocity: Velocity, elapsed: TimeDelta) -> Position {
+        if let Some(exponential) = self.as_exponential() {
+            return exponential.displacement_after(velocity, elapsed);
+        }
+
+        // the distance still to go only depends on the velocity left
+        self.distance(velocity) - self.distance(self.velocity_after(velocity, elapsed))
+    }
+
+    fn distance(&self, velocity: Velocity) -> Position {
+        if let Some(exponential) = self.as_exponential() {
+            return exponential.distance(velocity);
+        }
+
+        let power = 3.0 - self.exponent;
+
+        (velocity.abs().powf(power) / (power * self.coefficient)).copysign(velocity)
+    }
+
+    fn velocity_for_distance(&self, distance: Position) -> Velocity {
+        if let Some(exponential) = self.as_exponential() {
+            return exponential.velocity_for_distance(distance);
+        }
+
+        let power = 3.0 - self.exponent;
+
+        (distance.abs() * power * self.coefficient).powf(1.0 / power).copysign(distance)
+    }
+}
+
+impl Deceleration for Exponential {
+    fn velocity_after(&self, velocity: Velocity, elapsed: TimeDelta) -> Velocity {
+        let remaining = velocity * self.rate.powf(elapsed);
+
+        if remaining.abs() < REST_VELOCITY {
+            0.0
+        } else {
+            remaining
+        }
+    }
+
+    fn displacement_after(&self, velocity: Velocity, elapsed: TimeDelta) -> Position {
+        let speed = velocity.abs();
+        if speed < REST_VELOCITY {
+            return 0.0;
+        }
+
+        let stops_after = (REST_VELOCITY / speed).ln() / self.rate.ln();
+        let elapsed = elapsed.min(stops_after);
+
+        (speed * (1.0 - self.rate.powf(elapsed)) / -self.rate.ln()).copysign(velocity)
+    }
+
+    fn distance(&self, velocity: Velocity) -> Position {
+        let speed = (velocity.abs() - REST_VELOCITY).max(0.0);
+
+        (speed / -self.rate.ln()).copysign(velocity)
+    }
+
+    fn velocity_for_distance(&self, distance: Position) -> Velocity {
+        if distance == 0.0 {
+            return 0.0;
+        }
+
+        (distance.abs() * -self.rate.ln() + REST_VELOCITY).copysign(distance)
+    }
+}
+
+impl Deceleration for Constant {
+    fn velocity_after(&self, velocity: Velocity, elapsed: TimeDelta) -> Velocity {
+        (velocity.abs() - self.deceleration * elapsed).max(0.0).copysign(velocity)
+    }
+
+    fn displacement_after(&self, velocity: Velocity, elapsed: TimeDelta) -> Position {
+        let speed = velocity.abs();
+        let elapsed = elapsed.min(speed / self.deceleration);
+
+        (speed * elapsed - self.deceleration * elapsed * elapsed / 2.0).copysign(velocity)
+    }
+
+    fn distance(&self, velocity: Velocity) -> Position {
+        (velocity * velocity / (2.0 * self.deceleration)).copysign(velocity)
+    }
+
+    fn velocity_for_distance(&self, distance: Position) -> Velocity {
+        (2.0 * self.deceleration * distance.abs()).sqrt().copysign(distance)
+    }
+}
@@ -0,0 +1,60 @@
+/*!
+ * Named device profiles, for input devices that don't fit one of the built in Source variants
+ *
+ * A device profile is a Source::Device holding an interned name. Its tuning lives in the
+ * profiles of a Config like that of any other source, and can be given in ini files as a
+ * [device.<name>] section. The [devices] section maps device name strings, as reported by the
+ * platform, to profiles, so that Scrollview::set_source_for_device() can pick one automatically
+ */
+
+use std::sync::RwLock;
+
+use crate::Source;
+
+lazy_static! {
+    static ref NAMES: RwLock<Vec<String>> = RwLock::new(Vec::new());
+}
+
+/// Identifies a named device profile. Ids are interned, so the same name always gives the
+/// same id within a process
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DeviceId(u32);
+
+impl DeviceId {
+    pub fn named(name: &str) -> DeviceId {
+        if let Some(index) = NAMES.read().expect("Couldn't lock device names").iter().position(|n| n == name) {
+            return DeviceId(index as u32);
+        }
+
+        let mut names = NAMES.write().expect("Couldn't lock device names");
+        // someone else may have added it between the locks
+        let index = names.iter().position(|n| n == name).unwrap_or_else(|| {
+            names.push(name.to_owned());
+            names.len() - 1
+        });
+
+        DeviceId(index as u32)
+    }
+
+    pub fn name(&self) -> String {
+        NAMES.read().expect("Couldn't lock device names")[self.0 as usize].clone()
+    }
+}
+
+/// An entry of the device match table, choosing a profile for devices whose name contains
+/// the pattern (ignoring case)
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeviceMatch {
+    pub pattern: String,
+    pub source: Source,
+}
+
+impl DeviceMatch {
+    pub fn new(pattern: &str, source: Source) -> DeviceMatch {
+        DeviceMatch { pattern: pattern.to_owned(), source }
+    }
+
+    pub fn matches(&self, device: &str) -> bool {
+        device.to_lowercase().contains(&self.pattern.to_lowercase())
+    }
+}
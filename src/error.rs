@@ -0,0 +1,43 @@
+/// Errors that can be returned from the public scrollview interface
+///
+/// Bad input is rejected at the API boundary so that a single malformed
+/// event can not take down the event loop of the embedding application
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// An event was pushed without a timestamp
+    MissingTimestamp,
+    /// An event was pushed with a zero timestamp, timestamps must be greater than zero
+    ZeroTimestamp,
+    /// A NaN or infinite value was given where a finite number was expected
+    NonFinite(f64),
+    /// An animation was requested with a negative duration
+    NegativeDuration(f64),
+    /// The user config directory could not be determined on this platform
+    NoConfigDir,
+    /// A config file was asked for explicitly but couldn't be read
+    ConfigUnreadable(std::path::PathBuf, String),
+    /// A config had problems and was not applied, each of them is listed
+    InvalidConfig(Vec<crate::Diagnostic>),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::MissingTimestamp => write!(f, "event was pushed without a timestamp"),
+            Error::ZeroTimestamp => write!(f, "event was pushed with a zero timestamp"),
+            Error::NonFinite(value) => write!(f, "expected a finite value, got {}", value),
+            Error::NegativeDuration(duration) => write!(f, "animation duration can't be negative, got {}", duration),
+            Error::NoConfigDir => write!(f, "couldn't determine the user config directory"),
+            Error::ConfigUnreadable(path, reason) => write!(f, "couldn't read config file {}: {}", path.display(), reason),
+            Error::InvalidConfig(diagnostics) => {
+                write!(f, "invalid config")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n  {}", diagnostic)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl std::error::Error for Error {}
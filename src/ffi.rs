@@ -0,0 +1,469 @@
+/*!
+ * C interface to libscroll, implementing the rlscroll_* externals declared in libscroll.rei
+ *
+ * Scrollviews are handed across the boundary as opaque pointers created by
+ * rlscroll_create_scrollview() and released by rlscroll_destroy_scrollview().
+ * Functions that can fail return one of the RLSCROLL_* status codes, output
+ * functions return 0 when given a null handle.
+ *
+ * The matching header lives in include/libscroll.h, and can be regenerated with
+ * `cbindgen --config cbindgen.toml --output include/libscroll.h`
+ */
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::sync::Mutex;
+
+use crate::{reload_config, Axis, AxisVector, Capabilities, ConfigWatcher, Error, ScrollPhase, Scrollview, Source};
+
+pub const RLSCROLL_OK: c_int = 0;
+pub const RLSCROLL_ERR_NULL_HANDLE: c_int = -1;
+pub const RLSCROLL_ERR_MISSING_TIMESTAMP: c_int = -2;
+pub const RLSCROLL_ERR_ZERO_TIMESTAMP: c_int = -3;
+pub const RLSCROLL_ERR_NON_FINITE: c_int = -4;
+pub const RLSCROLL_ERR_NO_CONFIG_DIR: c_int = -5;
+pub const RLSCROLL_ERR_NEGATIVE_DURATION: c_int = -6;
+pub const RLSCROLL_ERR_CONFIG_UNREADABLE: c_int = -7;
+pub const RLSCROLL_ERR_INVALID_CONFIG: c_int = -8;
+
+pub const RLSCROLL_PHASE_IDLE: c_int = 0;
+pub const RLSCROLL_PHASE_TRACKING: c_int = 1;
+pub const RLSCROLL_PHASE_FLINGING: c_int = 2;
+pub const RLSCROLL_PHASE_BOUNCING: c_int = 3;
+pub const RLSCROLL_PHASE_ANIMATING: c_int = 4;
+
+/// Opaque handle to a scrollview, exposed to C as rlscroll_scrollview
+///
+/// Wraps the scrollview together with the last sampled position, so that the
+/// per-axis output functions can be called one after another for a single frame
+/// without advancing the scrollview twice
+pub struct ScrollviewHandle {
+    scrollview: Scrollview,
+
+    sampled_at: Option<u64>,
+    position: AxisVector<f64>,
+
+    // position as of the last rlscroll_get_pan_* call for that axis
+    reported: AxisVector<f64>,
+}
+
+impl ScrollviewHandle {
+    fn position_at(&mut self, timestamp: u64) -> AxisVector<f64> {
+        if self.sampled_at != Some(timestamp) {
+            self.position = self.scrollview.sample(timestamp);
+            self.sampled_at = Some(timestamp);
+        }
+
+        self.position
+    }
+}
+
+lazy_static! {
+    static ref WATCHER: Mutex<ConfigWatcher> = Mutex::new(ConfigWatcher::new());
+}
+
+fn status_of(result: Result<(), Error>) -> c_int {
+    match result {
+        Ok(()) => RLSCROLL_OK,
+        Err(Error::MissingTimestamp) => RLSCROLL_ERR_MISSING_TIMESTAMP,
+        Err(Error::ZeroTimestamp) => RLSCROLL_ERR_ZERO_TIMESTAMP,
+        Err(Error::NonFinite(_)) => RLSCROLL_ERR_NON_FINITE,
+        Err(Error::NoConfigDir) => RLSCROLL_ERR_NO_CONFIG_DIR,
+        Err(Error::NegativeDuration(_)) => RLSCROLL_ERR_NEGATIVE_DURATION,
+        Err(Error::ConfigUnreadable(..)) => RLSCROLL_ERR_CONFIG_UNREADABLE,
+        Err(Error::InvalidConfig(_)) => RLSCROLL_ERR_INVALID_CONFIG,
+    }
+}
+
+unsafe fn with_handle<R>(handle: *mut ScrollviewHandle, default: R, f: impl FnOnce(&mut ScrollviewHandle) -> R) -> R {
+    match handle.as_mut() {
+        Some(handle) => f(handle),
+        None => default,
+    }
+}
+
+unsafe fn set_source(handle: *mut ScrollviewHandle, source: Source) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| {
+        h.scrollview.set_source(source);
+        RLSCROLL_OK
+    })
+}
+
+// ScrollView
+
+/// Allocate a new scrollview using the global config
+///
+/// The returned handle must be released with rlscroll_destroy_scrollview()
+#[no_mangle]
+pub extern "C" fn rlscroll_create_scrollview() -> *mut ScrollviewHandle {
+    let handle = ScrollviewHandle {
+        scrollview: Scrollview::new(),
+        sampled_at: None,
+        position: AxisVector::default(),
+        reported: AxisVector::default(),
+    };
+
+    Box::into_raw(Box::new(handle))
+}
+
+/// Release a scrollview created with rlscroll_create_scrollview()
+///
+/// # Safety
+/// handle must be null or a pointer returned by rlscroll_create_scrollview() that
+/// has not yet been destroyed
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_destroy_scrollview(handle: *mut ScrollviewHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_geometry(
+    handle: *mut ScrollviewHandle,
+    content_height: f64,
+    content_width: f64,
+    viewport_height: f64,
+    viewport_width: f64,
+) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| {
+        status_of(h.scrollview.set_geometry(content_height, content_width, viewport_height, viewport_width))
+    })
+}
+
+/// Returns 1 if the scrollview should keep being sampled in absence of events, 0 otherwise
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_animating(handle: *mut ScrollviewHandle) -> c_int {
+    with_handle(handle, 0, |h| h.scrollview.animating() as c_int)
+}
+
+/// Re-read the global config file, see reload_config()
+///
+/// Scrollviews pick up the new config on their next sample
+#[no_mangle]
+pub extern "C" fn rlscroll_reload_config() -> c_int {
+    status_of(reload_config())
+}
+
+/// Reload the global config file if it changed since the last call, see ConfigWatcher
+///
+/// Returns 1 if the config was reloaded, 0 if the file is unchanged, or an RLSCROLL_ERR_* code.
+/// The first call only records the current state of the file
+#[no_mangle]
+pub extern "C" fn rlscroll_poll_config() -> c_int {
+    let mut watcher = WATCHER.lock().expect("Couldn't lock config watcher");
+
+    match watcher.poll() {
+        Ok(reloaded) => reloaded as c_int,
+        Err(e) => status_of(Err(e)),
+    }
+}
+
+/// Set the application whose [app.<name>] config sections apply from the next reload,
+/// or clear it if name is null
+///
+/// # Safety
+/// name must be null or a valid nul terminated string
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_application_name(name: *const c_char) -> c_int {
+    if name.is_null() {
+        crate::set_application_name(None);
+        return RLSCROLL_OK;
+    }
+
+    match CStr::from_ptr(name).to_str() {
+        Ok(name) => {
+            crate::set_application_name(Some(name));
+            RLSCROLL_OK
+        },
+        Err(_) => RLSCROLL_ERR_INVALID_CONFIG,
+    }
+}
+
+/// Override a value of the global config by its ini key, see set_runtime_override()
+///
+/// # Safety
+/// key must be a valid nul terminated string
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_runtime_override(key: *const c_char, value: f64) -> c_int {
+    if key.is_null() {
+        return RLSCROLL_ERR_INVALID_CONFIG;
+    }
+
+    match CStr::from_ptr(key).to_str() {
+        Ok(key) => status_of(crate::set_runtime_override(key, value)),
+        Err(_) => RLSCROLL_ERR_INVALID_CONFIG,
+    }
+}
+
+// Source
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_source_undefined(handle: *mut ScrollviewHandle) -> c_int {
+    set_source(handle, Source::Undefined)
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_source_touchscreen(handle: *mut ScrollviewHandle) -> c_int {
+    set_source(handle, Source::Touchscreen)
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_source_touchpad(handle: *mut ScrollviewHandle) -> c_int {
+    set_source(handle, Source::Touchpad)
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_source_mousewheel(handle: *mut ScrollviewHandle) -> c_int {
+    set_source(handle, Source::Mousewheel)
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_source_mousewheel_precise(handle: *mut ScrollviewHandle) -> c_int {
+    set_source(handle, Source::PreciseMousewheel)
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_source_passthrough(handle: *mut ScrollviewHandle) -> c_int {
+    set_source(handle, Source::Passthrough)
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_source_passthrough_kinetic(handle: *mut ScrollviewHandle) -> c_int {
+    set_source(handle, Source::KineticPassthrough)
+}
+
+/// Set the source of a scrollview from the device match table of its config, see
+/// Scrollview::set_source_for_device()
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview(), and device
+/// must be a valid nul terminated string
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_source_for_device(handle: *mut ScrollviewHandle, device: *const c_char) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| {
+        if device.is_null() {
+            return RLSCROLL_ERR_INVALID_CONFIG;
+        }
+
+        match CStr::from_ptr(device).to_str() {
+            Ok(device) => {
+                h.scrollview.set_source_for_device(device);
+                RLSCROLL_OK
+            },
+            Err(_) => RLSCROLL_ERR_INVALID_CONFIG,
+        }
+    })
+}
+
+/// Force the capabilities of a scrollview regardless of its source, each flag is 0 or 1
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_set_capabilities(
+    handle: *mut ScrollviewHandle,
+    overscrolls: c_int,
+    kinetic: c_int,
+    accelerates: c_int,
+) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| {
+        h.scrollview.set_capabilities(Some(Capabilities {
+            overscrolls: overscrolls != 0,
+            kinetic: kinetic != 0,
+            accelerates: accelerates != 0,
+        }));
+        RLSCROLL_OK
+    })
+}
+
+/// Go back to the capabilities configured for the source in use
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_reset_capabilities(handle: *mut ScrollviewHandle) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| {
+        h.scrollview.set_capabilities(None);
+        RLSCROLL_OK
+    })
+}
+
+// Events
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_signal_pan_x(handle: *mut ScrollviewHandle, amount: f64, timestamp: u64) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| {
+        status_of(h.scrollview.push_pan(Axis::Horizontal, amount, Some(timestamp)))
+    })
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_signal_pan_y(handle: *mut ScrollviewHandle, amount: f64, timestamp: u64) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| {
+        status_of(h.scrollview.push_pan(Axis::Vertical, amount, Some(timestamp)))
+    })
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_signal_release(handle: *mut ScrollviewHandle, timestamp: u64) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| {
+        status_of(h.scrollview.push_fling(Some(timestamp)))
+    })
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_signal_interrupt(handle: *mut ScrollviewHandle, timestamp: u64) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| {
+        status_of(h.scrollview.push_interrupt(Some(timestamp)))
+    })
+}
+
+// Output
+
+/// Horizontal distance moved since the last call to this function
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_get_pan_x(handle: *mut ScrollviewHandle, timestamp: u64) -> f64 {
+    with_handle(handle, 0.0, |h| {
+        let x = h.position_at(timestamp).x;
+        let pan = x - h.reported.x;
+        h.reported.x = x;
+        pan
+    })
+}
+
+/// Vertical distance moved since the last call to this function
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_get_pan_y(handle: *mut ScrollviewHandle, timestamp: u64) -> f64 {
+    with_handle(handle, 0.0, |h| {
+        let y = h.position_at(timestamp).y;
+        let pan = y - h.reported.y;
+        h.reported.y = y;
+        pan
+    })
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_get_pos_x(handle: *mut ScrollviewHandle, timestamp: u64) -> f64 {
+    with_handle(handle, 0.0, |h| h.position_at(timestamp).x)
+}
+
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_get_pos_y(handle: *mut ScrollviewHandle, timestamp: u64) -> f64 {
+    with_handle(handle, 0.0, |h| h.position_at(timestamp).y)
+}
+
+/// Horizontal velocity of the content in px/ms, see Scrollview::velocity()
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_get_velocity_x(handle: *mut ScrollviewHandle, timestamp: u64) -> f64 {
+    with_handle(handle, 0.0, |h| {
+        h.position_at(timestamp);
+        h.scrollview.velocity(timestamp).x
+    })
+}
+
+/// Vertical velocity of the content in px/ms, see Scrollview::velocity()
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_get_velocity_y(handle: *mut ScrollviewHandle, timestamp: u64) -> f64 {
+    with_handle(handle, 0.0, |h| {
+        h.position_at(timestamp);
+        h.scrollview.velocity(timestamp).y
+    })
+}
+
+/// One of the RLSCROLL_PHASE_* values for the horizontal axis, see Scrollview::phase()
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_get_phase_x(handle: *mut ScrollviewHandle) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| phase_of(h.scrollview.phase(Axis::Horizontal)))
+}
+
+/// One of the RLSCROLL_PHASE_* values for the vertical axis, see Scrollview::phase()
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_get_phase_y(handle: *mut ScrollviewHandle) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| phase_of(h.scrollview.phase(Axis::Vertical)))
+}
+
+fn phase_of(phase: ScrollPhase) -> c_int {
+    match phase {
+        ScrollPhase::Idle => RLSCROLL_PHASE_IDLE,
+        ScrollPhase::Tracking => RLSCROLL_PHASE_TRACKING,
+        ScrollPhase::Flinging => RLSCROLL_PHASE_FLINGING,
+        ScrollPhase::Bouncing => RLSCROLL_PHASE_BOUNCING,
+        ScrollPhase::Animating => RLSCROLL_PHASE_ANIMATING,
+    }
+}
+
+// Manual
+
+/// Move the content by the given offset immediately, see Scrollview::force_pan()
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_force_pan(handle: *mut ScrollviewHandle, dx: f64, dy: f64) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| {
+        h.sampled_at = None;
+        status_of(h.scrollview.force_pan(dx, dy))
+    })
+}
+
+/// Move the content to the given position immediately, see Scrollview::jump_to()
+///
+/// # Safety
+/// handle must be null or a live pointer returned by rlscroll_create_scrollview()
+#[no_mangle]
+pub unsafe extern "C" fn rlscroll_force_jump(handle: *mut ScrollviewHandle, x: f64, y: f64) -> c_int {
+    with_handle(handle, RLSCROLL_ERR_NULL_HANDLE, |h| {
+        h.sampled_at = None;
+        status_of(h.scrollview.jump_to(AxisVector::new(x, y)))
+    })
+}
@@ -0,0 +1,35 @@
+/*!
+ * Minimal ini reader that keeps track of where everything came from, so that problems
+ * in a config file can be reported against the line they are on
+ *
+ * Comments start with ; anywhere on a line, or # at the start of one. Keys are
+ * `key = value` pairs belonging to the last [section] header above them
+ */
+
+/// A single line of an ini file
+#[derive(Clone, Debug, PartialEq)]
+pub enum Line<'a> {
+    Section(&'a str),
+    Entry(&'a str, &'a str),
+    Malformed(&'a str),
+}
+
+/// The meaningful lines of an ini file along with their (1-based) line numbers, skipping
+/// blank lines and comments
+pub fn lines(contents: &str) -> impl Iterator<Item = (usize, Line<'_>)> {
+    contents.lines().enumerate().filter_map(|(index, line)| {
+        let content = line.split(';').next().unwrap_or("").trim();
+
+        let parsed = if content.is_empty() || content.starts_with('#') {
+            return None;
+        } else if content.starts_with('[') && content.ends_with(']') {
+            Line::Section(content[1..content.len() - 1].trim())
+        } else if let Some(split) = content.find('=') {
+            Line::Entry(content[..split].trim(), content[split + 1..].trim())
+        } else {
+            Line::Malformed(content)
+        };
+
+        Some((index + 1, parsed))
+    })
+}
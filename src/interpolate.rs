@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use std::sync::Arc;
 //use std::cell::RwLock;
 use std::borrow::Borrow;
 
@@ -10,7 +11,16 @@ type TimeDeltaMicros = u64;
 type Velocity = f64;
 type Position = f64;
 
-use crate::CONFIG as sconfig;
+use crate::{AxisState, Capabilities, Config, ScrollPhase};
+use crate::animation::{Animation, Easing};
+use crate::deceleration::Deceleration;
+use crate::pan::{Hermite, PanEvent, PanInterpolation};
+use crate::snap::SnapPoints;
+use crate::spring::Spring;
+
+// Fraction of a step that the last step of a sample may run over rather than leave behind
+const STEP_ROUNDING: f64 = 1e-6;
+
 
 /*const TICKS_PER_TIMUNIT: f64 = 0.5;
 
@@ -55,54 +65,26 @@ enum Phase {
     Inactive,
     Interpolating,
     Released(Time), // the velocity and time the release was done at
+    Animating, // driven by self.animation rather than by events
 }
 
+#[derive(Clone, Copy)]
 enum TrackPosition {
     Top,
     Bottom,
 }
 
-enum BounceState {
-    Bouncing(TrackPosition),
-    Normal,
-}
-
-#[derive(Clone, Copy, Debug, PartialOrd)]
-struct Event {
-    time: Time, // microseconds since interpolator init
-    //delta: f64, // distance represented by this event
-    value: f64, // the current absolute "position" of the event
-    //bezier_forward: Option<bezier::Curve<geo::Coord2>>,
-}
-
-impl std::fmt::Display for Event {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}: {})", self.time, self.value)
-    }
-}
-
-impl std::cmp::Ord for Event {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let cmp_time = self.time.partial_cmp(&other.time).expect("NaN in time field of an event");
-
-        match cmp_time {
-            std::cmp::Ordering::Equal => self.value.partial_cmp(&other.value).expect("NaN in value field of an event"),
-            other => other
-        }
-    }
-}
-
-impl std::cmp::PartialEq for Event {
-    fn eq(&self, other: &Self) -> bool {
-        self.value == other.value && self.time == other.time
-    }
+/// A released fling, solved in closed form from the moment it was released or went past an edge
+#[derive(Clone, Copy)]
+enum Fling {
+    /// Slowing down with the Deceleration from `velocity` (px/ms) at `start`, until it drops
+    /// under MIN_VELOCITY_TO_IDLE `duration` ms later and comes to rest at `rest`
+    Coasting { start: Time, velocity: f64, duration: TimeDelta, rest: Position },
+    /// Pulled back onto the edge by the Spring from `displacement` past it and `velocity`
+    /// (px/ms) at `start`
+    Bouncing { start: Time, edge: TrackPosition, displacement: Position, velocity: f64 },
 }
 
-impl std::cmp::Eq for Event {}
-
-/*impl std::cmp::PartialOrd for Event {
-    fn partial_cmp(&self*/
-
 #[derive(Clone, Copy)]
 struct Sample {
     time: Time,
@@ -124,7 +106,9 @@ pub struct Interpolator {
     //events: RangedMap<Timestamp, Event>,
     //samples: Vec<Timestamp>,
     
-    events: VecDeque<Event>,
+    events: Vec<PanEvent>,
+    // turns the events into the position that tracking velocity is measured from
+    pan_interpolation: Arc<dyn PanInterpolation>,
     samples: VecDeque<Sample>,
     pan_start_time: Time,
     current_phase: Phase,
@@ -134,13 +118,36 @@ pub struct Interpolator {
     track_bound_lower: f64,
     track_initial_pos: f64,
 
-    bouncing: BounceState,
+    // length of the viewport along this axis, used to line up snap points
+    viewport: f64,
+
+    snap: Option<SnapPoints>,
+
+    // when set, flings come to rest on multiples of the viewport length
+    paging: bool,
+
+    // the trajectory of the current fling, while Released
+    fling: Option<Fling>,
+
+    animation: Option<Animation>,
 
     min_tick_period: TimeDelta,
 
     last_value: f64,
     flips_same_value: u64,
     source: crate::Source,
+    // the config as given, and the same with the profile for the current source applied
+    base_config: Config,
+    config: Config,
+    capabilities: Capabilities,
+    // set through Scrollview::set_capabilities(), takes precedence over the source and config
+    forced_capabilities: Option<Capabilities>,
+    deceleration: Arc<dyn Deceleration>,
+    // set through Scrollview::set_deceleration(), takes precedence over the config
+    forced_deceleration: Option<Arc<dyn Deceleration>>,
+    spring: Spring,
+    // set through Scrollview::set_spring(), takes precedence over the config
+    forced_spring: Option<Spring>,
     //events_y: RangedMap<Timestamp, Event>,
 
 }
@@ -148,7 +155,56 @@ pub struct Interpolator {
 impl Interpolator {
     pub fn set_source(&mut self, source: crate::Source) {
         //println!("Sets source to {:?}", source);
+        // the trajectory of a fling was planned with the old physics, so one in progress is
+        // planned again from where it was last sampled. Its velocity is kept in px/ms, as the
+        // TIMESTEP it is measured in may change too
+        let flinging = match (self.current_phase, self.fling, self.samples.back()) {
+            (Phase::Released(_), Some(_), Some(&last)) => Some((last, self.to_px_per_ms(last.velocity))),
+            _ => None,
+        };
+
         self.source = source;
+        self.config = self.base_config.for_source(source);
+        self.capabilities = self.forced_capabilities.unwrap_or_else(|| Capabilities::resolve(source, &self.base_config));
+        self.deceleration = self.forced_deceleration.clone().unwrap_or_else(|| self.config.deceleration());
+        self.spring = self.forced_spring.unwrap_or_else(|| self.config.spring());
+
+        if let Some((last, velocity)) = flinging {
+            let last = Sample { velocity: self.from_px_per_ms(velocity), ..last };
+            if let Some(back) = self.samples.back_mut() {
+                back.velocity = last.velocity;
+            }
+            self.plan_fling(last);
+        }
+    }
+
+    /// Replaces the tunables used by this axis, takes effect on the next sample
+    pub fn set_config(&mut self, config: Config) {
+        self.base_config = config;
+        self.set_source(self.source);
+    }
+
+    pub fn set_capabilities(&mut self, capabilities: Option<Capabilities>) {
+        self.forced_capabilities = capabilities;
+        self.set_source(self.source);
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    pub fn set_deceleration(&mut self, deceleration: Option<Arc<dyn Deceleration>>) {
+        self.forced_deceleration = deceleration;
+        self.set_source(self.source);
+    }
+
+    pub fn set_spring(&mut self, spring: Option<Spring>) {
+        self.forced_spring = spring;
+        self.set_source(self.source);
+    }
+
+    pub fn set_pan_interpolation(&mut self, pan_interpolation: Arc<dyn PanInterpolation>) {
+        self.pan_interpolation = pan_interpolation;
     }
 
     pub fn print_events(&self) {
@@ -159,10 +215,11 @@ impl Interpolator {
                      String::new(), |s, evt| { s.to_owned() + &evt.to_string()[..] }));
     }
 
-    pub fn new(redistributable: bool, track_bounds: (f64, f64), initial_position: f64) -> Interpolator {
+    pub fn new(redistributable: bool, track_bounds: (f64, f64), initial_position: f64, config: Config) -> Interpolator {
         Interpolator {
             redistributable,
-            events: VecDeque::with_capacity(5),
+            events: Vec::with_capacity(5),
+            pan_interpolation: Arc::new(Hermite),
             samples: VecDeque::new(),
             pan_start_time: 0.0,
             min_tick_period: f64::INFINITY,
@@ -170,53 +227,48 @@ impl Interpolator {
             track_bound_lower: track_bounds.0,
             track_bound_upper: track_bounds.1,
             track_initial_pos: initial_position,
+            viewport: 0.0,
+            snap: None,
+            paging: false,
+            fling: None,
             flips_same_value: 0,
             last_value: 0.0,
-            bouncing: BounceState::Normal,
+            animation: None,
             source: crate::Source::Undefined,
+            config: config.for_source(crate::Source::Undefined),
+            capabilities: Capabilities::resolve(crate::Source::Undefined, &config),
+            forced_capabilities: None,
+            deceleration: config.for_source(crate::Source::Undefined).deceleration(),
+            forced_deceleration: None,
+            spring: config.for_source(crate::Source::Undefined).spring(),
+            forced_spring: None,
+            base_config: config,
         }
     }
 
     pub fn sample(&mut self, time: Time) -> Position {
-        let config = sconfig.read().unwrap();
-
         self.prevent_coast(time);
         //let time = time + 33.0;
 
+        if let (Phase::Animating, Some(animation)) = (self.current_phase, self.animation) {
+            return self.sample_animation(time, animation);
+        }
+
         //let velocity = self.sample_velocity(time);
 
-        let last_sample = self.samples.back().map(|&evt| evt).unwrap_or(Sample { time, velocity: 0.0, position: 0.0 });
+        let last_sample = self.samples.back().map(|&evt| evt).unwrap_or(Sample { time, velocity: 0.0, position: self.track_initial_pos });
 
         /*for step in last_sample.timestamp..timestamp {
         }*/
 
         //let time_range = timestamp - last_sample.timestamp;
-        let iter = iter_range(last_sample.time + config.SHIFT_WINDOW_MS, time + config.SHIFT_WINDOW_MS, config.TIMESTEP);
-
-        //let mut pos_delta = 0.0;
-
-        let mut cur_position = last_sample.position;
-        let mut cur_velocity = last_sample.velocity;
-        //println!("Starts {} {}", cur_position_step, cur_velocity_step);
-        for (start, end) in iter {
-            let stepped_velocity = self.step_velocity(start, end, cur_position, cur_velocity);
-            //let approx_velocity = (stepped_velocity + cur_velocity_step) / 2.0;
-
-            let velocity_per_step = (stepped_velocity + cur_velocity) / config.TIMESTEP; // TODO: for now
-
-            //println!("Velocity approx samples to {}", approx_vel);
-
-            let time_delta = end - start;
-
-            let integral = time_delta * velocity_per_step;
-
-
-            cur_position += integral;
-            cur_velocity = stepped_velocity;
-            /*println!("Integrates over {}, {} with approx_vel {} to reach {}",
-                     start, end, approx_vel, integral);*/
-        }
-        //println!("Ends {} {}", cur_position_step, cur_velocity_step);
+        let (mut cur_position, mut cur_velocity) = match (self.current_phase, self.fling) {
+            // a released fling has a closed form, only tracking needs stepping through
+            (Phase::Released(_), Some(fling)) => self.advance_fling(fling, time),
+            (Phase::Interpolating, _) => self.step_to(last_sample, time),
+            // nothing moves the content once it has come to rest, however long ago that was
+            _ => (last_sample.position, 0.0),
+        };
         //println!("Minticks is {}", self.min_tick_period);
 
         /*if cur_position > self.track_bound_upper && cur_velocity > 0.0 {
@@ -231,6 +283,12 @@ impl Interpolator {
             cur_velocity = 0.0;
         }*/
 
+        if !cur_position.is_finite() {
+            // hold the last good position rather than handing NaN to the caller
+            cur_position = last_sample.position;
+            cur_velocity = 0.0;
+        }
+
         self.samples.push_back(Sample { time, velocity: cur_velocity, position: cur_position });
 
         //self.check_idle(position);
@@ -253,41 +311,93 @@ impl Interpolator {
         cur_position
     }
 
+    /// Samples the position at the given time along with the velocity and overscroll there
+    pub fn sample_state(&mut self, time: Time) -> AxisState {
+        let position = self.sample(time);
+
+        AxisState {
+            position,
+            velocity: self.current_velocity(),
+            overscroll: position - self.clamp_to_track(position),
+            phase: self.phase(),
+        }
+    }
+
+    /// Velocity of the content in px/ms at the given time, advancing the samples up to it if needed
+    pub fn velocity_at(&mut self, time: Time) -> f64 {
+        self.position_at(time);
+
+        self.current_velocity()
+    }
+
+    pub fn phase(&self) -> ScrollPhase {
+        let position = self.samples.back().map(|s| s.position).unwrap_or(self.track_initial_pos);
+
+        match self.current_phase {
+            Phase::Inactive => ScrollPhase::Idle,
+            Phase::Interpolating => ScrollPhase::Tracking,
+            // a fling released past the edge is bouncing from the start, before it is sampled
+            Phase::Released(_) if self.is_bouncing() || self.outside_bounds(position) => ScrollPhase::Bouncing,
+            Phase::Released(_) => ScrollPhase::Flinging,
+            Phase::Animating => ScrollPhase::Animating,
+        }
+    }
+
     pub fn cull(&mut self) {
-        let config = sconfig.read().unwrap();
+        let config = &self.config;
 
         while self.samples.len() > config.SAMPLE_EXPIRY_COUNT {
             self.samples.pop_front();
         }
-        while self.events.len() > config.EVENT_EXPIRY_COUNT {
-            self.events.pop_front();
+        if self.events.len() > config.EVENT_EXPIRY_COUNT {
+            let expired = self.events.len() - config.EVENT_EXPIRY_COUNT;
+            self.events.drain(..expired);
         }
     }
 
     pub fn signal_fling(&mut self, time: Time) {
         //println!("Fling at {}", time);
+
+        // bring the samples up to the moment of release, so that the fling is planned from the
+        // exact position and velocity it will start with
+        let release = match self.samples.back() {
+            Some(last) if last.time < time => {
+                self.sample(time);
+                self.samples.back().cloned()
+            },
+            last => last.cloned(),
+        };
+        let release = release.unwrap_or(Sample { time, velocity: 0.0, position: self.track_initial_pos });
+
         self.current_phase = Phase::Released(time);
+        self.animation = None;
+        self.plan_fling(release);
 
         //self.flush(time);
     }
 
     pub fn signal_interrupt(&mut self, time: Time) {
-        println!("Interrupt at {}", time);
         //panic!("Interrupt not impl");
         self.current_phase = Phase::Inactive;
+        self.animation = None;
+        self.fling = None;
         self.flush(time);
         self.min_tick_period = f64::INFINITY;
     }
 
     pub fn signal_pan(&mut self, time: Time, delta: f64) {
         //println!("Signal pan at {} for {}", time, delta);
-        if time == 0.0 {
-            panic!("can't pass zero timestamps into signal_pan");
+        if time == 0.0 || !time.is_finite() || !delta.is_finite() {
+            // Scrollview rejects these before they get here, but an event that can't be placed
+            // on the track is better dropped than allowed to poison every later sample
+            return;
         }
         //println!("Push pan");
         self.current_phase = Phase::Interpolating;
+        self.animation = None;
+        self.fling = None;
 
-        let (prev_val, prev_time) = self.events.back().map(|evt| (evt.value, evt.time)).unwrap_or((self.track_initial_pos, f64::NEG_INFINITY));
+        let (prev_val, prev_time) = self.events.last().map(|evt| (evt.value, evt.time)).unwrap_or((self.track_initial_pos, f64::NEG_INFINITY));
 
         let current_val = prev_val + delta;
 
@@ -298,11 +408,11 @@ impl Interpolator {
 
             let redistributed = self.events.pop_back().unwrap();
 
-            let prev_prev = self.events.back().map(|&evt| evt.clone()).unwrap_or(Event { value: f64::NAN, time: time - TIMESTEP * 2.0 });
+            let prev_prev = self.events.back().map(|&evt| evt.clone()).unwrap_or(PanEvent { value: f64::NAN, time: time - TIMESTEP * 2.0 });
 
             let redis_time = prev_prev.time + (time - prev_prev.time) / 2.0;
 
-            self.events.push_back(Event { value: redistributed.value, time: redis_time });
+            self.events.push_back(PanEvent { value: redistributed.value, time: redis_time });
 
             self.samples.retain(|s| s.time < prev_prev.time); // invalidate samples that relied on old data*/
 
@@ -312,12 +422,12 @@ impl Interpolator {
             //
             // also remove any samples that rely on the bad data
             self.samples.retain(|s| s.time < prev_time);
-            self.events.pop_back();
+            self.events.pop();
         } else {
             self.min_tick_period = time - prev_time;
         }
 
-        self.events.push_back(Event { value: current_val, time });
+        self.events.push(PanEvent { value: current_val, time });
     }
 
     pub fn animating(&self) -> bool {
@@ -331,13 +441,363 @@ impl Interpolator {
         r
     }
 
-    pub fn set_geometry(&mut self, min: f64, max: f64) {
+    pub fn set_geometry(&mut self, min: f64, max: f64, viewport: f64) {
         self.track_bound_upper = max;
         self.track_bound_lower = min;
+        self.viewport = viewport;
+    }
+
+    /// Where the content is expected to come to rest given what is known now
+    ///
+    /// During a fling this is the end of its trajectory, or the edge it bounces back onto if
+    /// that is past the end of the track, during an animation its target, otherwise the
+    /// current position
+    pub fn predicted_rest(&self) -> Position {
+        match (self.current_phase, self.fling, self.animation) {
+            (Phase::Released(_), Some(Fling::Coasting { rest, .. }), _) => self.clamp_to_track(rest),
+            (Phase::Released(_), Some(Fling::Bouncing { edge, .. }), _) => self.edge_of(edge),
+            (Phase::Animating, _, Some(animation)) => animation.target(),
+            _ => self.samples.back().map(|s| s.position).unwrap_or(self.track_initial_pos),
+        }
+    }
+
+    /// Set the points flings on this axis should come to rest on, or None to let them rest anywhere
+    pub fn set_snap(&mut self, snap: Option<SnapPoints>) {
+        self.snap = snap;
+    }
+
+    /// Make flings on this axis come to rest on a page boundary, taking precedence over snap points
+    pub fn set_paging(&mut self, paging: bool) {
+        self.paging = paging;
+    }
+
+    /// Move the content to `position` (clamped to the track) immediately, dropping
+    /// any gesture, fling or animation in progress
+    pub fn jump(&mut self, position: Position) {
+        let position = self.clamp_to_track(position);
+
+        self.events.clear();
+        self.samples.clear();
+        self.current_phase = Phase::Inactive;
+        self.animation = None;
+        self.fling = None;
+        self.min_tick_period = f64::INFINITY;
+        self.flips_same_value = 0;
+
+        // with no samples left, the next sample starts here
+        self.track_initial_pos = position;
+    }
+
+    /// Offset the content by `delta` without animating, see jump()
+    pub fn offset(&mut self, delta: Position) {
+        let current = self.samples.back().map(|s| s.position).unwrap_or(self.track_initial_pos);
+
+        self.jump(current + delta);
+    }
+
+    /// Start a timed move from wherever the content is at `time` to `target`,
+    /// clamped to the track. Cancelled by any following pan, fling or interrupt
+    pub fn animate_to(&mut self, time: Time, target: Position, duration: TimeDelta, easing: Easing) {
+        let from = self.position_at(time);
+        let to = self.clamp_to_track(target);
+
+        self.animation = Some(Animation::new(time, duration, from, to, easing));
+        self.current_phase = Phase::Animating;
+        self.fling = None;
+    }
+
+    /// Same as animate_to, but relative to the target of any animation in
+    /// progress so that repeated calls accumulate instead of restarting from a midpoint
+    pub fn animate_by(&mut self, time: Time, delta: Position, duration: TimeDelta, easing: Easing) {
+        let base = match (self.current_phase, self.animation) {
+            (Phase::Animating, Some(animation)) => animation.target(),
+            _ => self.position_at(time),
+        };
+
+        self.animate_to(time, base + delta, duration, easing);
     }
 }
 // Private impl
 impl Interpolator {
+    /// Samples store velocity as half of the displacement over one TIMESTEP (see the
+    /// integration in sample()), this converts a velocity in px/ms into that unit
+    fn from_px_per_ms(&self, velocity: f64) -> Velocity {
+        velocity * self.config.TIMESTEP / 2.0
+    }
+
+    fn to_px_per_ms(&self, velocity: Velocity) -> f64 {
+        velocity * 2.0 / self.config.TIMESTEP
+    }
+
+    /// Velocity as of the last sample in px/ms, 0 once nothing is moving the content
+    fn current_velocity(&self) -> f64 {
+        match (self.current_phase, self.samples.back()) {
+            (Phase::Inactive, _) | (_, None) => 0.0,
+            (_, Some(last)) => self.to_px_per_ms(last.velocity),
+        }
+    }
+
+    fn clamp_to_track(&self, position: Position) -> Position {
+        position.min(self.track_bound_upper).max(self.track_bound_lower)
+    }
+
+    /// Where the content is at the given time, advancing the samples up to it if needed
+    fn position_at(&mut self, time: Time) -> Position {
+        match self.samples.back() {
+            Some(last) if last.time < time => self.sample(time),
+            Some(last) => last.position,
+            None => self.track_initial_pos,
+        }
+    }
+
+    /// Works out the trajectory of a fling released with the given state, steering it onto
+    /// a page or snap point if one applies
+    ///
+    /// Flings that would stop past the end of the track go past the edge and bounce back
+    /// onto it, and ones released in overscroll start out bouncing
+    fn plan_fling(&mut self, release: Sample) {
+        let velocity = self.to_px_per_ms(Self::sanitize(release.velocity));
+
+        if self.outside_bounds(release.position) {
+            let edge = self.track_position_of(release.position);
+            let displacement = release.position - self.edge_of(edge);
+
+            self.fling = Some(Fling::Bouncing { start: release.time, edge, displacement, velocity });
+            return;
+        }
+
+        // without coasting the content stays where it was released unless it snaps somewhere
+        let velocity = if self.capabilities.kinetic { velocity } else { 0.0 };
+        let rest = release.position + self.deceleration.displacement_after(velocity, self.coasting_time(velocity));
+
+        let target = self.page_target(release, rest).or_else(|| {
+            self.snap.as_ref().and_then(|snap| {
+                snap.select(rest, self.viewport, self.track_bound_lower, self.track_bound_upper)
+            })
+        });
+
+        let (velocity, rest) = match target {
+            Some(target) => (self.velocity_for_distance(target - release.position), target),
+            None => (velocity, rest),
+        };
+
+        if velocity != self.to_px_per_ms(release.velocity) {
+            // the fling starts out at the velocity it was steered to
+            let released = self.from_px_per_ms(velocity);
+            if let Some(last) = self.samples.back_mut() {
+                last.velocity = released;
+            }
+        }
+
+        let duration = self.coasting_time(velocity);
+        self.fling = Some(Fling::Coasting { start: release.time, velocity, duration, rest });
+    }
+
+    /// Page boundary a fling released with the given state and that would naturally stop at
+    /// `rest` should stop at, if paging
+    ///
+    /// Moves at most one page past the page boundary behind the release position, unless
+    /// the release velocity is above PAGE_SKIP_VELOCITY
+    fn page_target(&self, release: Sample, rest: Position) -> Option<Position> {
+        let page = self.viewport;
+
+        if !self.paging || page.is_nan() || page <= 0.0 {
+            return None;
+        }
+
+        let mut index = (rest / page).round();
+
+        if self.to_px_per_ms(release.velocity).abs() < self.config.PAGE_SKIP_VELOCITY {
+            // the page being flung away from, and the one next to it in the direction of the fling
+            let (first, last) = if release.velocity > 0.0 {
+                let leaving = (release.position / page).floor();
+                (leaving, leaving + 1.0)
+            } else if release.velocity < 0.0 {
+                let leaving = (release.position / page).ceil();
+                (leaving - 1.0, leaving)
+            } else {
+                let nearest = (release.position / page).round();
+                (nearest, nearest)
+            };
+
+            index = index.max(first).min(last);
+        }
+
+        Some(self.clamp_to_track(index * page))
+    }
+
+    /// Integrates the tracking velocity from the given sample up to `time` in TIMESTEP steps
+    fn step_to(&mut self, from: Sample, time: Time) -> (Position, Velocity) {
+        let timestep = self.config.TIMESTEP;
+        let shift_window = self.config.SHIFT_WINDOW_MS;
+
+        let iter = iter_range(from.time + shift_window, time + shift_window, timestep);
+
+        let mut cur_position = from.position;
+        let mut cur_velocity = from.velocity;
+        //println!("Starts {} {}", cur_position_step, cur_velocity_step);
+        for (start, end) in iter {
+            let stepped_velocity = self.step_velocity(start, end, cur_position, cur_velocity);
+
+            let velocity_per_step = (stepped_velocity + cur_velocity) / timestep; // TODO: for now
+
+            let time_delta = end - start;
+
+            let integral = time_delta * velocity_per_step;
+
+            cur_position += integral;
+            cur_velocity = stepped_velocity;
+        }
+        //println!("Ends {} {}", cur_position_step, cur_velocity_step);
+
+        (cur_position, cur_velocity)
+    }
+
+    /// Where a released fling is at `time`, solved from the start of its trajectory in one go
+    /// rather than stepped to
+    ///
+    /// Coasts with the Deceleration until it passes the edge of the track, and from the
+    /// moment it does the Spring pulls it back. Both come to a stop with a velocity of exactly 0
+    fn advance_fling(&mut self, fling: Fling, time: Time) -> (Position, Velocity) {
+        match fling {
+            Fling::Coasting { start, velocity, duration, rest } => {
+                // measured back from where it comes to rest, so that it ends up exactly there
+                let travel = |elapsed: TimeDelta| self.deceleration.displacement_after(velocity, elapsed.clamp(0.0, duration));
+                let position_after = |elapsed: TimeDelta| rest - (travel(duration) - travel(elapsed));
+
+                if time < start {
+                    // time went backwards, nothing has had time to decay
+                    return (position_after(0.0) + (time - start) * velocity, self.from_px_per_ms(velocity));
+                }
+
+                let elapsed = time - start;
+                let position = position_after(elapsed);
+
+                if !self.outside_bounds(position) {
+                    let velocity = if elapsed < duration { self.deceleration.velocity_after(velocity, elapsed) } else { 0.0 };
+                    return (position, self.from_px_per_ms(velocity));
+                }
+
+                // goes past the edge along the way, the spring takes over from there
+                let edge = self.track_position_of(position);
+                let from = position_after(0.0);
+                let reached = if self.outside_bounds(from) {
+                    // the track shrank under the content
+                    0.0
+                } else {
+                    self.deceleration.time_to_travel(velocity, self.edge_of(edge) - from).unwrap_or(elapsed).min(elapsed)
+                };
+
+                let bouncing = Fling::Bouncing {
+                    start: start + reached,
+                    edge,
+                    displacement: position_after(reached) - self.edge_of(edge),
+                    velocity: self.deceleration.velocity_after(velocity, reached),
+                };
+                self.fling = Some(bouncing);
+
+                self.advance_fling(bouncing, time)
+            },
+            Fling::Bouncing { start, edge, displacement, velocity } => {
+                let trackposition = edge;
+                let edge = self.edge_of(trackposition);
+
+                if time < start {
+                    return (edge + displacement + (time - start) * velocity, self.from_px_per_ms(velocity));
+                }
+
+                let (displacement, velocity) = self.spring.advance(displacement, velocity, time - start);
+
+                let back_inside = match trackposition {
+                    TrackPosition::Top => displacement > 0.0,
+                    TrackPosition::Bottom => displacement < 0.0,
+                };
+
+                if back_inside && !self.spring.overshoots() {
+                    // released in overscroll and thrown back faster than the spring alone would bring
+                    // it, a spring that doesn't overshoot stops it on the edge rather than let it through
+                    return (edge, 0.0);
+                }
+
+                if self.bounce_settled(displacement, velocity) {
+                    // the spring only ever approaches the edge, rest on it once there's nothing left to see
+                    return (edge, 0.0);
+                }
+
+                (edge + displacement, self.from_px_per_ms(Self::sanitize(velocity)))
+            },
+        }
+    }
+
+    /// Whether a bounce has come to rest, with neither where it is nor where its velocity
+    /// (px/ms) could carry it more than half a pixel away from the edge
+    fn bounce_settled(&self, displacement: Position, velocity: f64) -> bool {
+        let reach = velocity / self.spring.frequency();
+
+        Self::rounds_to_zero(displacement) && Self::rounds_to_zero(reach)
+    }
+
+    /// The edge of the track a position outside it has gone past
+    fn track_position_of(&self, position: Position) -> TrackPosition {
+        if position > self.track_bound_upper {
+            TrackPosition::Bottom
+        } else {
+            TrackPosition::Top
+        }
+    }
+
+    fn edge_of(&self, trackposition: TrackPosition) -> Position {
+        match trackposition {
+            TrackPosition::Top => self.track_bound_lower,
+            TrackPosition::Bottom => self.track_bound_upper,
+        }
+    }
+
+    fn is_bouncing(&self) -> bool {
+        matches!(self.fling, Some(Fling::Bouncing { .. }))
+    }
+
+    /// How long a fling released at `velocity` (px/ms) coasts for before it slows to
+    /// MIN_VELOCITY_TO_IDLE and stops
+    fn coasting_time(&self, velocity: f64) -> TimeDelta {
+        let min = self.to_px_per_ms(self.config.MIN_VELOCITY_TO_IDLE).copysign(velocity);
+
+        if velocity.abs() <= min.abs() {
+            return 0.0;
+        }
+
+        let distance = self.deceleration.distance(velocity) - self.deceleration.distance(min);
+
+        self.deceleration.time_to_travel(velocity, distance).unwrap_or(0.0)
+    }
+
+    /// Release velocity (px/ms) with which a fling coasts the given distance before it slows
+    /// to MIN_VELOCITY_TO_IDLE and stops
+    fn velocity_for_distance(&self, distance: Position) -> f64 {
+        if distance == 0.0 {
+            return 0.0;
+        }
+
+        let min = self.to_px_per_ms(self.config.MIN_VELOCITY_TO_IDLE).copysign(distance);
+
+        self.deceleration.velocity_for_distance(distance + self.deceleration.distance(min))
+    }
+
+    fn sample_animation(&mut self, time: Time, animation: Animation) -> Position {
+        let position = animation.position_at(time);
+        let velocity = self.from_px_per_ms(animation.velocity_at(time));
+
+        self.samples.push_back(Sample { time, velocity, position });
+        self.cull();
+
+        if animation.finished_at(time) {
+            self.animation = None;
+            self.current_phase = Phase::Inactive;
+        }
+
+        position
+    }
+
     /// Empties the sample and event lists, used for interrupt/fling
     /// when a group of samples is logically over (a single "gesture")
     fn flush(&mut self, time: Time) {
@@ -347,10 +807,16 @@ impl Interpolator {
     }
 
     fn check_idle(&mut self, position: Position, velocity: Velocity) {
-        let config = sconfig.read().unwrap();
+        let config = &self.config;
 
         match self.current_phase {
+            Phase::Released(_) if velocity == 0.0 => {
+                // the trajectory has brought the fling to a stop, or settled its bounce on the edge
+                self.go_idle();
+            },
+            Phase::Released(_) if self.is_bouncing() => {}
             Phase::Released(_) => {
+                // catches decelerations that stall without ever slowing past MIN_VELOCITY_TO_IDLE
                 if position == self.last_value || velocity.abs() < config.MIN_VELOCITY_TO_IDLE {
                     self.flips_same_value += 1;
                 } else {
@@ -359,17 +825,22 @@ impl Interpolator {
                 self.last_value = position;
 
                 if self.flips_same_value > config.FLIPS_TO_IDLE {
-                    eprintln!("Goes to idle");
-                    println!("check_idle goes to Inactive");
-                    self.current_phase = Phase::Inactive;
+                    self.go_idle();
                 }
             },
             Phase::Interpolating => {
                 self.flips_same_value = 0;
             },
-            Phase::Inactive => {}
+            Phase::Inactive | Phase::Animating => {}
         }
     }
+    /// Ends a fling, including any bounce it ended with
+    fn go_idle(&mut self) {
+        self.current_phase = Phase::Inactive;
+        self.fling = None;
+        self.flips_same_value = 0;
+    }
+
     /*fn check_idle(&mut self, velocity: Velocity) {
         if velocity < MIN_VELOCITY_TO_IDLE {
             println!("Libscroll idles with velocity {}", velocity);
@@ -378,18 +849,17 @@ impl Interpolator {
     }*/
 
     fn prevent_coast(&mut self, time: Time) {
-        let config = sconfig.read().unwrap();
+        let config = &self.config;
 
         match self.current_phase {
             Phase::Interpolating => match self.events.len() {
                 0 => {}
                 _ => {
-                    let evt = self.events.back().expect("Events was empty despite len > 0");
+                    let evt = self.events.last().expect("Events was empty despite len > 0");
                     let delta = (time - evt.time).abs();
                     if delta > self.min_tick_period * config.TICKS_TO_COAST || delta > config.MAX_MS_WITHOUT_ZERO_INJECTION {
                         // inject event
                         //println!("\n\n\n\n\nCLAMPs velocity to prevent coast");
-                        println!("Clamps to prevent coast. Delta {} evt {} min_tick_period {}", delta, evt, self.min_tick_period);
                         //self.current_phase = Phase::Inactive;
                         self.signal_interrupt(time);
                     }
@@ -400,105 +870,43 @@ impl Interpolator {
     }
 
     fn interpolate(&self, time: Time) -> Velocity {
-        let config = sconfig.read().unwrap();
-
-        let first_before = self
-            .events
-            .iter()
-            .filter(|evt| evt.time < time)
-            // round is ok since timestamps have no greater enqueing precision than integrals
-            .max_by(|evt_a, evt_b| (evt_a.time as u64).cmp(&(evt_b.time as u64)));
-
-        let first_after = self
-            .events
-            .iter()
-            .filter(|evt| evt.time >= time)
-            .min_by(|evt_a, evt_b| (evt_a.time as u64).cmp(&(evt_b.time as u64)));
-
-        let second_before = match first_before {
-            None => None,
-            Some(first) => {
-                self
-                    .events
-                    .iter()
-                    .filter(|evt| evt.time < first.time)
-                    .max_by(|evt_a, evt_b| (evt_a.time as u64).cmp(&(evt_b.time as u64)))
-            }
-        };
-
-        let second_after = match first_after {
-            None => None,
-            Some(first) => {
-                self
-                    .events
-                    .iter()
-                    .filter(|evt| evt.time < first.time)
-                    .min_by(|evt_a, evt_b| (evt_a.time as u64).cmp(&(evt_b.time as u64)))
-            }
-        };
-
-        //let events: Vec<&Event> = vec![&first_before, &first_after, &second_before, &second_after]
-        let events: Vec<&Event> = vec![&second_before, &first_before, &first_after]
-            .into_iter()
-            .filter_map(|&evt| evt) // trim Nones
-            .collect();
+        let config = &self.config;
 
-        let result = match events.len() {
-            0 => {
+        match self.events.as_slice() {
+            [] => {
                 //println!("Interpolate returns 0 as no events exist");
                 0.0
             },
-            1 => {
-                //println!("Interpolate returns 0, as can't get slope of single event");
-                let evt = events.first().expect("no elements in size 1 vec");
+            [evt] => {
+                // can't get slope of single event
                 //evt.value * MULTIPLY_FIRST_EVENT / (time - evt.time + 1.0)
                 //FIRST_EVENT_SLOPE * (time - evt.time) + evt.value
                 evt.value / config.MAX_MS_WITHOUT_ZERO_INJECTION
-            }
-            //1 => Self::interpolate_constant(&events, time),
-            2 => Self::interpolate_linear(&events, time),
-            3 => Self::interpolate_linear_averaging(&events, time),
-            4 => Self::interpolate_hermite(&events, time),
-            _ => panic!("Programming error: events len greater than 4"),
-        };
-
-        if events.len() == 1 {
-            //println!("Sampling imprecisely, only one event available");
-        }
-
-        if result == 0.0 {
-            //println!("interpolate returned zero. Events vec is {:?} and time is {}. All events is {:?}", events, time, self.events);
+            },
+            events => self.pan_interpolation.position_at(events, time),
         }
-
-        //println!("Interpolates result {} with evt count {}", result, events.len());
-
-        result
     }
 
     fn outside_bounds(&self, position: Position) -> bool {
-        let config = sconfig.read().unwrap();
-
         position > self.track_bound_upper || position < self.track_bound_lower
     }
 
     fn short_circuit_single_event(&self) -> Position /* delta */ {
-        let config = sconfig.read().unwrap();
-
-        self.events.back().map(|evt| evt.value).unwrap_or(0.0)
+        self.events.last().map(|evt| evt.value).unwrap_or(0.0)
     }
 
     fn fling_boost(&self, velocity: Velocity) -> Velocity {
-        let config = sconfig.read().unwrap();
+        let config = &self.config;
 
         velocity * config.FLING_BOOST_CONSTANT_FACTOR
     }
 
     fn handle_overscroll(&self, start: Time, end: Time, position: Position, velocity: Velocity) -> Velocity {
-        let config = sconfig.read().unwrap();
+        let config = &self.config;
 
         if self.outside_bounds(position) {
             //velocity.abs().powf(0.6).copysign(velocity)
-            if self.source.overscrolls() {
+            if self.capabilities.overscrolls {
                 let outside_by = if position > self.track_bound_upper {
                     if velocity < 0.0 {
                         return velocity;
@@ -524,10 +932,7 @@ impl Interpolator {
                 }*/
 
                 //(abs_vel - reduction_amount).copysign(velocity)
-                if r_velocity.is_nan() {
-                    panic!("handle_overscroll tried to return NaN");
-                }
-                r_velocity
+                Self::sanitize(r_velocity)
             } else {
                 if velocity < 0.0 && position < self.track_bound_upper {
                     0.0
@@ -543,9 +948,9 @@ impl Interpolator {
     }
 
     fn accelerate(&self, velocity: Velocity) -> Velocity {
-        let config = sconfig.read().unwrap();
+        let config = &self.config;
         //velocity
-        if self.source.accelerates() {
+        if self.capabilities.accelerates {
             (velocity / config.ACCEL_DECEL_DESCRIMINANT).abs().powf(config.ACCELERATION_EXPONENT).copysign(velocity) * config.ACCEL_DECEL_DESCRIMINANT
         } else {
             velocity
@@ -553,131 +958,26 @@ impl Interpolator {
     }
 
     fn pre_scale(&self, velocity: Velocity) -> Velocity {
-        let config = sconfig.read().unwrap();
+        let config = &self.config;
 
         velocity * config.PRE_ACCEL_SCALE_VELOCITY
     }
 
     fn post_scale(&self, velocity: Velocity) -> Velocity {
-        let config = sconfig.read().unwrap();
+        let config = &self.config;
 
         velocity * config.POST_ACCEL_SCALE_VELOCITY
     }
 
-    fn decay(&self, start: Time, end: Time, _position: Position, old_velocity: Velocity) -> Velocity {
-        if old_velocity.is_nan() {
-            panic!("given NaN velocity");
-        }
-
-        if old_velocity == 0.0 {
-            return 0.0;
-        }
-
-        let timedelta = end - start;
-        //println!("DECAY: {}, {}", timedelta, old_velocity);
-        let abs_vel = old_velocity.abs();
-
-        if timedelta < 0.0 {
-            panic!("Negative timedelta passed to decay");
-        }
-
-        //let slope = -0.00003 / (old_velocity.log2() + 1.0);
-        let friction_factor = if old_velocity != 0.0 {
-            old_velocity.abs().powf(1.3) / old_velocity.abs()
-        } else {
-            0.0
-        };
-                
-        let slope = -0.00009 * friction_factor;
-        let new_vel = abs_vel + slope * timedelta;
-
-        let floored = if new_vel < 0.0 {
-            0.0
-        } else {
-            new_vel.copysign(old_velocity)
-        };
-
-        //println!("PRODUCES {}", r);
-
-        if floored.abs() > old_velocity.abs() {
-            panic!("Somehow accelerated");
-        }
-
-        if floored.is_nan() {
-            panic!("tried to return NaN velocity. Given {}", old_velocity);
-        }
-
-        floored
-        //0.0
-
-
-        /*if velocity == 0.0 {
-            panic!();
-        }*/
-        //velocity * 0.9999
-        /*let slope = 0.99;
-        let slope = match velocity < 0.0 {
-            true => -slope,
-            false => slope,
-        };
-
-        let velocity = velocity + slope * timedelta;
-
-        if velocity < 0.0 {
-            0.0
-        } else {
-            velocity
-        }*/
-    }
-
-    fn bounce(&mut self, start: Time, end: Time, position: Position, old_velocity: Velocity) -> Velocity {
-        let config = sconfig.read().unwrap();
-
-        if self.outside_bounds(position) {
-            let trackposition = if position > self.track_bound_upper {
-                TrackPosition::Bottom
-            } else {
-                TrackPosition::Top
-            };
-
-            self.bouncing = BounceState::Bouncing(trackposition);
-        }
-
-        match &self.bouncing {
-            BounceState::Normal => old_velocity,
-            BounceState::Bouncing(trackposition) => {
-                if old_velocity.is_nan() {
-                    panic!("Given NaN velocity");
-                }
-
-                let displacement = match trackposition {
-                    TrackPosition::Top => position - self.track_bound_lower,
-                    TrackPosition::Bottom => position - self.track_bound_upper,
-                };
-
-                let force = -displacement * config.OVERSCROLL_SPRING_CONSTANT;
-                let timedelta = end - start;
-                let acceleration = force / config.CONTENT_MASS_VALUE;
-                let velocity = old_velocity + acceleration * timedelta;
-
-                let velocity = velocity * config.BOUNCE_DAMP_FACTOR;
-
-                if velocity.is_nan() {
-                    panic!("Velocity was NaN");
-                } else if velocity.is_infinite() {
-                    panic!();
-                }
-
-                velocity
-            }
-        }
-    }
-
     fn sample_velocity(&self, start: Time, end: Time) -> Velocity {
         let p1 = self.interpolate(start);
         let p2 = self.interpolate(end);
         let timedelta = end - start;
-        let vel = (p2 - p1) * timedelta; // units of TIMESTEP
+        let timestep = self.config.TIMESTEP;
+
+        // units of TIMESTEP. Normalized by the step length so that the short step ending each
+        // sample gives the same velocity as a full one, as a fling starts from that velocity
+        let vel = (p2 - p1) / timedelta * timestep * timestep;
         //println!("Velocity avg ({}, {}) to from ({}, {}) becomes {}", start, end, p1, p2, vel);
 
         //println!("\n\n\n\nGives velocity {}", vel);
@@ -692,28 +992,10 @@ impl Interpolator {
 
                 r
             },
-            Phase::Released(release_time) if release_time < start => {
-                let b = self.bounce(
-                    start,
-                    end,
-                    position,
-                    old_velocity);
-                let r = self.decay(
-                    start,
-                    end,
-                    position,
-                    b);
-
-                /*if r.abs() > old_velocity.abs() {
-                    panic!("Velocity increased during release");
-                }*/
-
-                r
-            },
+            // only the steps before a release are stepped through, see advance_fling()
             Phase::Interpolating | Phase::Released(_) => {
                 // short circuit velocity measurement, velocity is just the accelerated
                 // interpolation velocity
-                self.bouncing = BounceState::Normal;
                 let r = self.post_scale(
                             self.handle_overscroll(
                                 start,
@@ -724,87 +1006,33 @@ impl Interpolator {
                                         self.sample_velocity(start, end)))));
                 r
             },
-            Phase::Inactive => 0.0
+            Phase::Inactive | Phase::Animating => 0.0
         }
     }
 
     fn set_inactive(&mut self) {
         println!("set_inactive sets Inactive");
         self.current_phase = Phase::Inactive;
-        self.bouncing = BounceState::Normal;
+        self.fling = None;
         self.samples.clear();
     }
 }
 
 // static funcs
 impl Interpolator {
-    fn sample_linear(first: &Event, second: &Event, sample: Time) -> f64 {
-        let slope = Self::slope_of(first, second);
-
-        slope * (sample - first.time) + first.value
-    }
-
-    fn slope_of(first: &Event, second: &Event) -> f64 {
-        //println!("Slope_of asked to compute {}, {}", first, second);
-        if first.time == second.time {
-            0.0
+    /// Replaces NaN or infinite velocities with a stop, so that bad input
+    /// or degenerate geometry stalls the content instead of corrupting its position
+    fn sanitize(velocity: Velocity) -> Velocity {
+        if velocity.is_finite() {
+            velocity
         } else {
-            (first.value - second.value) / ((first.time as i64) - (second.time as i64)) as f64
+            0.0
         }
     }
 
     fn rounds_to_zero(val: f64) -> bool {
         val.abs() < 0.5
     }
-
-    fn interpolate_constant(events: &Vec<&Event>, at: Time) -> f64 {
-        let vel = events.first().expect("interpolate_constant given empty events vec").value;
-
-        //println!("Interpolating constant, gives {}", vel);
-
-        vel
-    }
-
-    fn interpolate_linear(events: &Vec<&Event>, at: Time) -> f64 {
-        //println!("Interpolating linear");
-        let first = events[0];
-        let second = events[1];
-
-        if first == second {
-            panic!("interpolate_linear given single event");
-        }
-
-        /*if first.value == second.value && first.value != 0.0 {
-            println!("Equal values in events");
-        }*/
-
-        Self::sample_linear(first, second, at)
-    }
-
-    fn interpolate_linear_averaging(events: &Vec<&Event>, at: Time) -> f64 {
-        //println!("Interpolating 3avg");
-        let first = events[1];
-        let second = events[2];
-        Self::interpolate_linear(&vec![first, second], at)
-        /*let mut events = events.clone();
-        events.sort();
-        let e1 = events[0];
-        let e2 = events[1];
-        let e3 = events[2];
-
-        let s1 = Self::interpolate_linear(&vec![e1, e2], at);
-        let s2 = Self::interpolate_linear(&vec![e2, e3], at);
-
-        let avg = (s1 + s2) / 2.0;
-
-        avg*/
-    }
-
-    fn interpolate_hermite(events: &Vec<&Event>, at: Time) -> f64 {
-        println!("Interpolating hermite");
-        panic!("not implemented");
-        0.0
-    }
 }
 
 struct TimestampIterator {
@@ -820,7 +1048,9 @@ impl Iterator for TimestampIterator {
     fn next(&mut self) -> Option<Self::Item> {
         if self.cur == self.end {
             None
-        } else if (self.end - self.cur) < self.step {
+        } else if (self.end - self.cur) < self.step * (1.0 + STEP_ROUNDING) {
+            // a remainder this close to a whole step is rounding error from adding up steps,
+            // left on its own it would be a sliver of a step with a meaningless velocity
             let r = (self.cur, self.end);
             self.cur = self.end;
             Some(r)
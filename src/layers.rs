@@ -0,0 +1,244 @@
+/*!
+ * Layered config resolution
+ *
+ * The effective config is built up from several layers, each of which only sets the
+ * values it cares about. Later layers take precedence over earlier ones, so a distro can
+ * ship defaults in /etc/xdg, the user can tweak some of them, and an application can
+ * pin a few for itself on top of both
+ */
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::config::Key;
+use crate::device::{DeviceId, DeviceMatch};
+use crate::{Capabilities, Config, Diagnostic, DiagnosticKind, Error, Source, ValueKind};
+
+/// Where an effective config value came from, ordered from lowest to highest precedence
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    /// The built in Config::default() values
+    Default,
+    /// The system wide config file, such as /etc/xdg/libscroll/config.ini
+    System,
+    /// The user's config file, or the file given with set_config_location() or $LIBSCROLL_CONFIG
+    User,
+    /// [app.<name>] sections of the config files, for the name given to set_application_name()
+    Application,
+    /// Overrides set by the application at runtime
+    Runtime,
+}
+
+/// The values set by a single layer, keyed by their ini key, along with the values it sets
+/// for specific sources and its device match table
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overrides {
+    values: BTreeMap<&'static str, f64>,
+    profiles: HashMap<Source, BTreeMap<&'static str, f64>>,
+    devices: Vec<DeviceMatch>,
+}
+
+impl Overrides {
+    pub fn new() -> Overrides {
+        Overrides::default()
+    }
+
+    /// Set a value by its ini key, such as "spring_stiffness"
+    ///
+    /// Returns Error::InvalidConfig if the key doesn't exist, or the value isn't of the right
+    /// type or is out of range
+    pub fn set(&mut self, key: &str, value: f64) -> Result<(), Error> {
+        let found = Self::validate(key, value)?;
+        self.insert(None, found, value);
+
+        Ok(())
+    }
+
+    /// Set a value that only applies while the given source is in use, see set()
+    pub fn set_for_source(&mut self, source: Source, key: &str, value: f64) -> Result<(), Error> {
+        let found = Self::validate(key, value)?;
+        self.insert(Some(source), found, value);
+
+        Ok(())
+    }
+
+    fn validate(key: &str, value: f64) -> Result<&'static Key, Error> {
+        let diagnostic = |kind| Error::InvalidConfig(vec![Diagnostic { line: None, key: key.to_owned(), kind }]);
+
+        let found = Key::find(key).ok_or_else(|| diagnostic(DiagnosticKind::UnknownKey { suggestion: Key::suggest(key) }))?;
+        if found.kind != ValueKind::Float && value.fract() != 0.0 {
+            return Err(diagnostic(DiagnosticKind::TypeError { expected: found.kind, found: value.to_string() }));
+        }
+        found.check(value).map_err(diagnostic)?;
+
+        Ok(found)
+    }
+
+    pub(crate) fn insert(&mut self, source: Option<Source>, key: &'static Key, value: f64) {
+        match source {
+            None => self.values.insert(key.name, value),
+            Some(source) => self.profiles.entry(source).or_default().insert(key.name, value),
+        };
+    }
+
+    /// Set values in the profile of the named device, keeping any it already has that aren't
+    /// given again, see Config::register_device()
+    pub fn register_device(&mut self, name: &str, capabilities: Capabilities, profile: &Overrides) -> Source {
+        let source = Source::Device(DeviceId::named(name));
+        let values = self.profiles.entry(source).or_default();
+        values.extend(profile.values.iter().map(|(&key, &value)| (key, value)));
+
+        for (key, value) in capability_values(capabilities) {
+            values.insert(key, value);
+        }
+
+        source
+    }
+
+    pub(crate) fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.values.extend(capability_values(capabilities));
+    }
+
+    /// Add an entry to the device match table, after the ones already there
+    pub fn match_device(&mut self, pattern: &str, source: Source) {
+        self.devices.push(DeviceMatch::new(pattern, source));
+    }
+
+    /// Remove the value set for a key, returning it if there was one
+    pub fn remove(&mut self, key: &str) -> Option<f64> {
+        self.values.remove(key)
+    }
+
+    /// Remove the value set for a key for the given source, returning it if there was one
+    pub fn remove_for_source(&mut self, source: Source, key: &str) -> Option<f64> {
+        self.profiles.get_mut(&source)?.remove(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<f64> {
+        self.values.get(key).cloned()
+    }
+
+    pub fn get_for_source(&self, source: Source, key: &str) -> Option<f64> {
+        self.profiles.get(&source)?.get(key).cloned()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.profiles.values().all(BTreeMap::is_empty) && self.devices.is_empty()
+    }
+
+    /// Values in `other` replace the ones already set here, and its device matches are
+    /// checked before the ones here
+    pub fn merge(&mut self, other: &Overrides) {
+        self.values.extend(other.values.iter().map(|(&key, &value)| (key, value)));
+        self.devices.splice(0..0, other.devices.iter().cloned());
+
+        for (&source, values) in &other.profiles {
+            self.profiles.entry(source).or_default().extend(values.iter().map(|(&key, &value)| (key, value)));
+        }
+    }
+
+    /// Applies the values that aren't specific to a source
+    ///
+    /// Deprecated keys go first, so that the keys replacing them win when both are given
+    pub(crate) fn apply_to(&self, config: &mut Config) {
+        let (deprecated, current): (Vec<_>, Vec<_>) = self.values.iter().partition(|(name, _)| Key::replacement(name).is_some());
+
+        for (name, &value) in deprecated.into_iter().chain(current) {
+            if let Some(key) = Key::find(name) {
+                (key.set)(config, value);
+            }
+        }
+    }
+
+    /// Adds the values specific to each source to the profiles of the config, and the device
+    /// matches ahead of the ones already in it
+    pub(crate) fn apply_profiles_to(&self, config: &mut Config) {
+        for (&source, values) in &self.profiles {
+            let profile = config.profiles.entry(source).or_default();
+            profile.values.extend(values.iter().map(|(&key, &value)| (key, value)));
+        }
+
+        config.devices.splice(0..0, self.devices.iter().cloned());
+    }
+}
+
+fn capability_values(capabilities: Capabilities) -> Vec<(&'static str, f64)> {
+    vec![
+        ("overscrolls", capabilities.overscrolls as u8 as f64),
+        ("kinetic", capabilities.kinetic as u8 as f64),
+        ("accelerates", capabilities.accelerates as u8 as f64),
+    ]
+}
+
+/// A stack of Overrides, one per Layer, that resolves into a single Config
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LayeredConfig {
+    layers: BTreeMap<Layer, Overrides>,
+}
+
+impl LayeredConfig {
+    pub fn new() -> LayeredConfig {
+        LayeredConfig::default()
+    }
+
+    /// The values set by the given layer, empty if it sets nothing
+    pub fn layer(&self, layer: Layer) -> Overrides {
+        self.layers.get(&layer).cloned().unwrap_or_default()
+    }
+
+    /// Mutable access to the values of a layer. Values set on Layer::Default replace the
+    /// built in defaults
+    pub fn layer_mut(&mut self, layer: Layer) -> &mut Overrides {
+        self.layers.entry(layer).or_default()
+    }
+
+    pub fn set_layer(&mut self, layer: Layer, overrides: Overrides) {
+        self.layers.insert(layer, overrides);
+    }
+
+    /// The effective config, with each layer applied over the ones below it
+    ///
+    /// The source profiles of a layer go on top of the general values of that same layer, but
+    /// under anything set by a higher layer, so a runtime override always wins
+    pub fn resolve(&self) -> Config {
+        let mut config = Config::default();
+        for overrides in self.layers.values() {
+            overrides.apply_to(&mut config);
+            // general values of this layer take over from the profile values of the ones below,
+            // including deprecated ones they replace
+            for profile in config.profiles.values_mut() {
+                profile.values.retain(|&name, _| {
+                    !overrides.values.contains_key(name)
+                        && !Key::replacement(name).is_some_and(|replacement| overrides.values.contains_key(replacement))
+                });
+            }
+            overrides.apply_profiles_to(&mut config);
+        }
+
+        config
+    }
+
+    /// The layer the effective value of the given ini key comes from, or None if there is no
+    /// such key
+    pub fn source_of(&self, key: &str) -> Option<Layer> {
+        Key::find(key)?;
+
+        Some(self.layers
+            .iter()
+            .rev()
+            .find(|(_, overrides)| overrides.get(key).is_some())
+            .map(|(&layer, _)| layer)
+            .unwrap_or(Layer::Default))
+    }
+
+    /// Like source_of(), but for the value in effect while the given source is in use
+    pub fn source_for(&self, source: Source, key: &str) -> Option<Layer> {
+        Key::find(key)?;
+
+        Some(self.layers
+            .iter()
+            .rev()
+            .find(|(_, overrides)| overrides.get_for_source(source, key).is_some() || overrides.get(key).is_some())
+            .map(|(&layer, _)| layer)
+            .unwrap_or(Layer::Default))
+    }
+}
@@ -14,96 +14,56 @@
 extern crate num;
 use std::f64;
 
-use app_dirs::*;
-
 #[macro_use]
 extern crate lazy_static;
 
-mod circular_backqueue;
+mod animation;
 
-mod interpolate;
-
-mod ranged_map;
+mod circular_backqueue;
 
-use std::ops;
-use interpolate::Interpolator;
+mod config;
 
-type Timestamp = u64;
+mod deceleration;
 
-use std::fs::File;
-use std::io::prelude::*;
-
-use std::sync::RwLock;
-use tini::Ini;
-
-const APP_INFO: AppInfo = AppInfo { name: "libscroll", author: "Sawyer Bergeron" };
-
-#[allow(non_snake_case)]
-pub struct Config {
-
-	pub EVENT_EXPIRY_COUNT: usize,
-	pub SAMPLE_EXPIRY_COUNT: usize,
-
-	pub TICKS_TO_COAST: f64,
-	pub TIMESTEP: f64,
-	pub MIN_VELOCITY_TO_IDLE: f64,
-	pub POST_ACCEL_SCALE_VELOCITY: f64,
-	pub PRE_ACCEL_SCALE_VELOCITY: f64,
-	pub SHIFT_WINDOW_MS: f64,
-	pub OVERSCROLL_ELASTICITY_COEFFICIENT: f64,
-	pub CONTENT_MASS_VALUE: f64,
-	pub OVERSCROLL_SPRING_CONSTANT: f64,
-	pub BOUNCE_DAMP_FACTOR: f64,
-	pub MAX_MS_WITHOUT_ZERO_INJECTION: f64,
-	pub MULTIPLY_FIRST_EVENT: f64,
-    pub ACCEL_DECEL_DESCRIMINANT: f64,
-    pub ACCELERATION_EXPONENT: f64,
-    pub FLING_BOOST_CONSTANT_FACTOR: f64,
-
-	pub FLIPS_TO_IDLE: u64,
-}
+mod device;
 
-impl Default for Config {
-    fn default() -> Self {
-        Config {
-            TIMESTEP: 0.1,
+mod error;
 
-            MIN_VELOCITY_TO_IDLE: 0.002,
+mod ffi;
 
-            EVENT_EXPIRY_COUNT: 20,
-            SAMPLE_EXPIRY_COUNT: 20,
+mod ini;
 
-            TICKS_TO_COAST: 1.6,
+mod layers;
 
-            FLIPS_TO_IDLE: 20,
+mod interpolate;
 
-            POST_ACCEL_SCALE_VELOCITY: 19.0,
-            PRE_ACCEL_SCALE_VELOCITY: 1.0,
+mod pan;
 
-            SHIFT_WINDOW_MS: 0.0,
+mod ranged_map;
 
-            OVERSCROLL_ELASTICITY_COEFFICIENT: 1.0,
+mod snap;
 
-            CONTENT_MASS_VALUE: 6000.0,
-            OVERSCROLL_SPRING_CONSTANT: 0.4,
+mod spring;
 
-            BOUNCE_DAMP_FACTOR: 0.9974,
+use std::ops;
+use std::sync::Arc;
+use interpolate::Interpolator;
 
-            MAX_MS_WITHOUT_ZERO_INJECTION: 150.0,
-
-            MULTIPLY_FIRST_EVENT: 500.0,
-            ACCEL_DECEL_DESCRIMINANT: 10.0,
-            ACCELERATION_EXPONENT: 1.4,
-            FLING_BOOST_CONSTANT_FACTOR: 2.0,
-        }
-    }
-}
-
-//thread_local!(static CONFIG: RefCell<Config> = RefCell::new(Config::default()));
-lazy_static! {
-    static ref CONFIG: RwLock<Config> = RwLock::new(Config::default());
-}
+pub use config::{
+    AllowedRange, Config, ConfigLocation, ConfigWatcher, Diagnostic, DiagnosticKind, ValueKind, CONFIG_ENV,
+    clear_runtime_override, config_source, config_source_for, last_load_error, register_device, reload_config,
+    set_application_name, set_config_location, set_runtime_override,
+};
+pub use deceleration::{Constant, Deceleration, DecelerationModel, Exponential, PowerLaw};
+pub use spring::Spring;
+pub use device::{DeviceId, DeviceMatch};
+pub use layers::{Layer, LayeredConfig, Overrides};
+pub use error::Error;
+pub use animation::Easing;
+pub use pan::{AveragedLinear, Hermite, LeastSquares, Linear, PanEvent, PanInterpolation};
+pub use snap::{SnapAlign, SnapMode, SnapPoints, SnapTargets};
 
+type Timestamp = u64;
 
 //#[macro_use]
 //extern crate smart_default;
@@ -152,6 +112,10 @@ pub struct Scrollview {
 
     current_source: Source,
 
+    config: Config,
+    // generation of the global config this scrollview last took, None if it has its own
+    global_generation: Option<u64>,
+
     dbg_amt_x: f64,
     dbg_amt_y: f64,
 
@@ -176,14 +140,13 @@ pub struct Scrollview {
 pub struct AxisVector<T> where T: num::Num, T: PartialOrd, T: Copy {
     pub x: T,
     pub y: T,
-
-    x_threshold: T,
-    y_threshold: T,
-    
-    decaying: bool,
 }
 
 impl<T> AxisVector<T> where T: num::Num, T: PartialOrd, T: Copy {
+    pub fn new(x: T, y: T) -> AxisVector<T> {
+        AxisVector { x, y }
+    }
+
     /*fn difference(self, other: AxisVector<T>) -> AxisVector<T> {
         AxisVector {
             x: self.x - other.x,
@@ -219,7 +182,6 @@ impl AxisVector<f64> {
         AxisVector {
             x: self.x * scalar,
             y: self.y * scalar,
-            ..self.clone()
         }
     }
 }
@@ -233,8 +195,6 @@ impl<T> ops::Add<AxisVector<T>> for AxisVector<T> where T: num::Num, T: PartialO
         AxisVector {
             x: self.x + rhs.x,
             y: self.y + rhs.y,
-            ..self
-
         }
     }
 }
@@ -247,6 +207,43 @@ impl<T> std::fmt::Display for AxisVector<T>
     }
 }
 
+/// What is moving the content along one axis, as of the last sample
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ScrollPhase {
+    /// At rest, nothing left to animate
+    #[default]
+    Idle,
+    /// Following a gesture in progress
+    Tracking,
+    /// Coasting to rest after a fling
+    Flinging,
+    /// Springing back from past the edge of the content
+    Bouncing,
+    /// Moving to the target of scroll_to() or scroll_by()
+    Animating,
+}
+
+/// What the content is doing along one axis, as of the time it was sampled
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AxisState {
+    /// Position of the content relative to the viewport in device pixels, as given by sample()
+    pub position: f64,
+    /// Velocity of the content in px/ms
+    pub velocity: f64,
+    /// How far the content is past the edge of the track, negative past the start and positive
+    /// past the end, 0 while within it
+    pub overscroll: f64,
+    /// What is moving the content along this axis
+    pub phase: ScrollPhase,
+}
+
+/// The state of both axes of a scrollview, see Scrollview::sample_state()
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScrollState {
+    pub x: AxisState,
+    pub y: AxisState,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum Axis {
     Horizontal,
@@ -254,7 +251,7 @@ pub enum Axis {
 }
 
 /// Pass along with any events to indicate what kind of device the event came from
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 //#[derive(Clone)]
 pub enum Source {
     /// Device type is unknown, assume nothing (very suboptimal to actually use this, should only
@@ -276,8 +273,12 @@ pub enum Source {
     Passthrough,
     /// Same as passthrough, but input fling events should trigger a kinetic fling animation
     KineticPassthrough,
-    /// The device type last used
+    /// The device type last used, set_source() keeps whichever concrete source was set before
+    /// (Undefined if there was none)
     Previous,
+    /// A named device profile, registered with register_device() or given as a [device.<name>]
+    /// section of the config. Treated like Undefined unless its profile says otherwise
+    Device(DeviceId),
 }
 
 impl Source {
@@ -310,35 +311,97 @@ impl Default for Source {
     fn default() -> Self { Source::Undefined }
 }
 
+/// How input from a source is treated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Content can be dragged or flung past its edges, and springs back
+    pub overscrolls: bool,
+    /// Releasing a gesture with push_fling() lets the content coast to a stop
+    pub kinetic: bool,
+    /// Fast movements are scaled up more than slow ones
+    pub accelerates: bool,
+}
+
+impl Capabilities {
+    /// The built in capabilities of a source
+    pub fn of(source: Source) -> Capabilities {
+        Capabilities {
+            overscrolls: source.overscrolls(),
+            kinetic: source.kinetic(),
+            accelerates: source.accelerates(),
+        }
+    }
+
+    /// The capabilities of a source with the OVERSCROLLS, KINETIC and ACCELERATES values of the
+    /// config applied over them, using the profile for that source
+    pub fn resolve(source: Source, config: &Config) -> Capabilities {
+        let config = config.for_source(source);
+        let defaults = Capabilities::of(source);
+
+        Capabilities {
+            overscrolls: config.OVERSCROLLS.unwrap_or(defaults.overscrolls),
+            kinetic: config.KINETIC.unwrap_or(defaults.kinetic),
+            accelerates: config.ACCELERATES.unwrap_or(defaults.accelerates),
+        }
+    }
+}
+
 // pub interface
 impl Scrollview {
     /// Gives the current best estimate for the position of the content relative to
     /// the viewport in device pixels
     pub fn sample(&mut self, timestamp: Timestamp) -> AxisVector<f64> {
+        self.refresh_config();
+
         if !DEBUG {
             AxisVector {
-                //x: self.x.sample(timestamp as f64) * VALUE_MULTIPLIER,
-                x: 0.0,
+                x: self.x.sample(timestamp as f64),
                 y: self.y.sample(timestamp as f64),
-                ..Default::default()
             }
         } else {
             AxisVector {
                 x: self.dbg_amt_x,
                 y: self.dbg_amt_y,
-                ..Default::default()
             }
         }
     }
+
+    /// Same as sample(), but also gives the velocity, overscroll and phase of each axis
+    pub fn sample_state(&mut self, timestamp: Timestamp) -> ScrollState {
+        self.refresh_config();
+
+        ScrollState {
+            x: self.x.sample_state(timestamp as f64),
+            y: self.y.sample_state(timestamp as f64),
+        }
+    }
     /// Create a new scrollview with default settings
     ///
     /// Warning: these settings are unlikely to be
     /// particularly useful, so set_geometry(), set_avg_frametime(), and any
     /// other relevant initialization functions still need to be used
+    ///
+    /// Uses the global config, which is read from the location set with set_config_location()
+    /// the first time a scrollview is created and only re-read by reload_config() or a
+    /// ConfigWatcher. The scrollview keeps following the global config, see follow_global_config()
     pub fn new() -> Scrollview {
-        eprintln!("Updating config...");
-        Self::update_config();
+        let mut scrollview = Self::from_config(Config::default());
+        scrollview.follow_global_config();
+        scrollview
+    }
+
+    /// Create a new scrollview that uses the provided config instead of the global one
+    ///
+    /// The config is owned by the scrollview, and is not affected by later reloads
+    /// of the global config. Returns Error::InvalidConfig listing every value that is out of
+    /// range, such as a TIMESTEP of 0 that would never finish stepping, see Config::diagnostics()
+    pub fn with_config(config: Config) -> Result<Scrollview, Error> {
+        config.validate()?;
 
+        Ok(Self::from_config(config))
+    }
+
+    fn from_config(config: Config) -> Scrollview {
         Scrollview {
             input_per_frame_log: circular_backqueue::ForgetfulLogQueue::new(SAMPLE_OVER_X_FRAMES),
             content_height: 0.0,
@@ -352,11 +415,41 @@ impl Scrollview {
             //time_to_pageflip: 0.0,
             //current_timestamp: 0,
             //interpolation_ratio: 0.0,
-            x: Interpolator::new(false, (0.0, 0.0), 0.0),
-            y: Interpolator::new(false, (0.0, 0.0), 0.0),
+            x: Interpolator::new(false, (0.0, 0.0), 0.0, config.clone()),
+            y: Interpolator::new(false, (0.0, 0.0), 0.0, config.clone()),
+            config,
+            global_generation: None,
         }
     }
 
+    /// Replace the config used by this scrollview and both of its axes
+    ///
+    /// The scrollview stops following the global config until follow_global_config() is called.
+    /// Returns Error::InvalidConfig and keeps the current config if any value is out of range
+    pub fn set_config(&mut self, config: Config) -> Result<(), Error> {
+        config.validate()?;
+
+        self.global_generation = None;
+        self.apply_config(config);
+
+        Ok(())
+    }
+
+    /// Switch to the global config, and keep switching to it whenever it is reloaded
+    ///
+    /// Reloads are picked up at the start of the next sample(), so a frame is always computed
+    /// with a single config. Ongoing flings continue from where they are with the new physics
+    pub fn follow_global_config(&mut self) {
+        let (config, generation) = config::global_config();
+        self.apply_config(config);
+        self.global_generation = Some(generation);
+    }
+
+    /// The config currently in use by this scrollview
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     /// Deletes/deinitializes the current scrollview
     ///
     /// Primarily intended for ffi use, Scrollview implements Drop
@@ -369,20 +462,46 @@ impl Scrollview {
     /// Set the geometry for the given scrollview
     ///
     /// Can be used both on scrollview initialization and on scrollview resize
+    ///
+    /// Returns Error::NonFinite and leaves the geometry unchanged if any dimension is NaN or
+    /// infinite
     pub fn set_geometry(
         &mut self,
         content_height: f64,
         content_width: f64,
         viewport_height: f64,
         viewport_width: f64,
-    ) {
+    ) -> Result<(), Error> {
+        for &dimension in &[content_height, content_width, viewport_height, viewport_width] {
+            finite(dimension)?;
+        }
+
         self.content_height = content_height;
         self.content_width = content_width;
         self.viewport_height = viewport_height;
         self.viewport_width = viewport_width;
 
-        self.x.set_geometry(0.0, (content_width - viewport_width) as f64);
-        self.y.set_geometry(0.0, (content_height - viewport_height) as f64);
+        self.x.set_geometry(0.0, (content_width - viewport_width) as f64, viewport_width);
+        self.y.set_geometry(0.0, (content_height - viewport_height) as f64, viewport_height);
+
+        Ok(())
+    }
+
+    /// Velocity of the content in px/ms at the given timestamp, advancing the scrollview up to
+    /// it like sample() does. Asking again for the same timestamp doesn't move the content
+    pub fn velocity(&mut self, timestamp: Timestamp) -> AxisVector<f64> {
+        self.refresh_config();
+
+        AxisVector::new(self.x.velocity_at(timestamp as f64), self.y.velocity_at(timestamp as f64))
+    }
+
+    /// What is moving the content along the given axis as of the last sample(), which is where
+    /// stalled gestures time out and flings come to rest
+    pub fn phase(&self, axis: Axis) -> ScrollPhase {
+        match axis {
+            Axis::Horizontal => self.x.phase(),
+            Axis::Vertical => self.y.phase(),
+        }
     }
 
     /// True if scrollview should continue to be polled
@@ -408,12 +527,17 @@ impl Scrollview {
     }
 
     /// Enqueue a pan event for the referenced scrollview
-    pub fn push_pan(&mut self, axis: Axis, amount: f64, timestamp: Option<u64>) {
-        //println!("push_pan with {:?}, {}, {}", axis, amount, timestamp.unwrap());
+    ///
+    /// Rejects events without a timestamp, with a zero timestamp, or with a non finite amount
+    pub fn push_pan(&mut self, axis: Axis, amount: f64, timestamp: Option<u64>) -> Result<(), Error> {
+        let timestamp = timestamp_of(timestamp)?;
+        let amount = finite(amount)?;
+
+        //println!("push_pan with {:?}, {}, {}", axis, amount, timestamp);
         if !DEBUG {
             match axis {
-                Axis::Horizontal => self.x.signal_pan(timestamp.unwrap() as f64, amount),
-                Axis::Vertical => self.y.signal_pan(timestamp.unwrap() as f64, amount),
+                Axis::Horizontal => self.x.signal_pan(timestamp as f64, amount),
+                Axis::Vertical => self.y.signal_pan(timestamp as f64, amount),
             }
         } else {
             match axis {
@@ -421,68 +545,252 @@ impl Scrollview {
                 Axis::Vertical => self.dbg_amt_y += amount,
             }
         }
+
+        Ok(())
     }
 
     /// Enqueue a fling (finger lift at any velocity) for the referenced scrollview
-    pub fn push_fling(&mut self, timestamp: Option<u64>) {
-        eprintln!("Updating config...");
-        Self::update_config();
-        println!("push_fling with {}", timestamp.unwrap());
+    pub fn push_fling(&mut self, timestamp: Option<u64>) -> Result<(), Error> {
+        let timestamp = timestamp_of(timestamp)?;
+
         //self.current_velocity.decay_start();
-        self.x.signal_fling(timestamp.unwrap() as f64);
-        self.y.signal_fling(timestamp.unwrap() as f64);
+        self.x.signal_fling(timestamp as f64);
+        self.y.signal_fling(timestamp as f64);
+
+        Ok(())
     }
 
     /// Enqueue a scroll interrupt (finger down at any time, gesture start) for the referenced
     /// scrollview
-    pub fn push_interrupt(&mut self, timestamp: Option<u64>) {
+    pub fn push_interrupt(&mut self, timestamp: Option<u64>) -> Result<(), Error> {
+        let timestamp = timestamp_of(timestamp)?;
+
         //self.pan_log_x.clear();
         //self.pan_log_y.clear();
         //self.current_velocity = AxisVector { x: 0.0, y: 0.0, ..self.current_velocity };
-        self.x.signal_interrupt(timestamp.unwrap() as f64);
-        self.y.signal_interrupt(timestamp.unwrap() as f64);
+        self.x.signal_interrupt(timestamp as f64);
+        self.y.signal_interrupt(timestamp as f64);
+
+        Ok(())
+    }
+
+    /// Smoothly scroll so that the content ends up at the given position, clamped to the
+    /// scrollable area set with set_geometry()
+    ///
+    /// The animation starts at timestamp and lasts for duration milliseconds, following the
+    /// given easing curve. animating() stays true until it completes, and any following
+    /// pan, fling or interrupt cancels it and takes over from wherever the content currently is
+    pub fn scroll_to(
+        &mut self,
+        position: AxisVector<f64>,
+        timestamp: Option<u64>,
+        duration: f64,
+        easing: Easing,
+    ) -> Result<(), Error> {
+        let timestamp = timestamp_of(timestamp)? as f64;
+        let (x, y) = (finite(position.x)?, finite(position.y)?);
+        let duration = duration_of(duration)?;
+
+        self.x.animate_to(timestamp, x, duration, easing);
+        self.y.animate_to(timestamp, y, duration, easing);
+
+        Ok(())
+    }
+
+    /// Smoothly scroll the content by the given offset, see scroll_to()
+    ///
+    /// If a scroll_to() or scroll_by() animation is already running, the offset is applied to
+    /// its target rather than to the current position, so repeated calls (such as from a held
+    /// down arrow key) add up
+    pub fn scroll_by(
+        &mut self,
+        delta: AxisVector<f64>,
+        timestamp: Option<u64>,
+        duration: f64,
+        easing: Easing,
+    ) -> Result<(), Error> {
+        let timestamp = timestamp_of(timestamp)? as f64;
+        let (dx, dy) = (finite(delta.x)?, finite(delta.y)?);
+        let duration = duration_of(duration)?;
+
+        self.x.animate_by(timestamp, dx, duration, easing);
+        self.y.animate_by(timestamp, dy, duration, easing);
+
+        Ok(())
+    }
+
+    /// Immediately move the content to the given position, clamped to the scrollable area set
+    /// with set_geometry()
+    ///
+    /// Any gesture, fling or animation in progress is dropped, so this is suitable for restoring
+    /// a saved position or jumping to a specific location in the content
+    pub fn jump_to(&mut self, position: AxisVector<f64>) -> Result<(), Error> {
+        let (x, y) = (finite(position.x)?, finite(position.y)?);
+
+        self.x.jump(x);
+        self.y.jump(y);
+
+        Ok(())
+    }
+
+    /// Immediately move the content by the given offset without animating, clamped to the
+    /// scrollable area set with set_geometry(). Like jump_to(), this stops any fling in progress
+    pub fn force_pan(&mut self, dx: f64, dy: f64) -> Result<(), Error> {
+        let (dx, dy) = (finite(dx)?, finite(dy)?);
+
+        self.x.offset(dx);
+        self.y.offset(dy);
+
+        Ok(())
+    }
+
+    /// Where the content is predicted to come to rest, in the same coordinates as sample()
+    ///
+    /// Right after push_fling() this is where the fling will land, accounting for snap points,
+    /// paging and the edges set with set_geometry(), so content there can be loaded ahead of
+    /// time. Once the fling completes, sample() returns exactly this position. While an
+    /// animation is running it is the animation target, otherwise the current position
+    pub fn predicted_rest_position(&self) -> AxisVector<f64> {
+        AxisVector::new(self.x.predicted_rest(), self.y.predicted_rest())
+    }
+
+    /// Set the points flings along the given axis come to rest on, or None to remove them
+    ///
+    /// When a fling is released with push_fling(), the position it would naturally come to
+    /// rest at is predicted and, if a snap point applies, the fling is steered so that it
+    /// decelerates onto that point instead. Snap points are given in content coordinates and
+    /// lined up with the viewport according to their SnapAlign
+    pub fn set_snap_points(&mut self, axis: Axis, snap: Option<SnapPoints>) {
+        match axis {
+            Axis::Horizontal => self.x.set_snap(snap),
+            Axis::Vertical => self.y.set_snap(snap),
+        }
+    }
+
+    /// Enable or disable paging along the given axis
+    ///
+    /// While paging, flings always come to rest on a multiple of the viewport size given to
+    /// set_geometry(), moving at most one page on from the page the fling was released in unless
+    /// released faster than the PAGE_SKIP_VELOCITY config value. Paging takes precedence over
+    /// snap points
+    pub fn set_paging(&mut self, axis: Axis, paging: bool) {
+        match axis {
+            Axis::Horizontal => self.x.set_paging(paging),
+            Axis::Vertical => self.y.set_paging(paging),
+        }
+    }
+
+    fn apply_config(&mut self, config: Config) {
+        self.x.set_config(config.clone());
+        self.y.set_config(config.clone());
+        self.config = config;
+    }
+
+    fn refresh_config(&mut self) {
+        if let Some(generation) = self.global_generation {
+            if generation != config::global_generation() {
+                self.follow_global_config();
+            }
+        }
+    }
+
+    /// Force the capabilities of this scrollview regardless of the source in use, or None to go
+    /// back to the ones configured for each source, see Capabilities::resolve()
+    pub fn set_capabilities(&mut self, capabilities: Option<Capabilities>) {
+        self.x.set_capabilities(capabilities);
+        self.y.set_capabilities(capabilities);
+    }
+
+    /// Make flings slow down by the given Deceleration regardless of the config, or None to go
+    /// back to the one the config chooses, see Config::deceleration()
+    pub fn set_deceleration(&mut self, deceleration: Option<Arc<dyn Deceleration>>) {
+        self.x.set_deceleration(deceleration.clone());
+        self.y.set_deceleration(deceleration);
+    }
+
+    /// Make overscrolled flings bounce back with the given Spring regardless of the config, or
+    /// None to go back to the one the config gives, see Config::spring()
+    ///
+    /// Returns Error::InvalidConfig and keeps the current spring if the given one would never
+    /// settle, see Spring::validate()
+    pub fn set_spring(&mut self, spring: Option<Spring>) -> Result<(), Error> {
+        if let Some(spring) = &spring {
+            spring.validate()?;
+        }
+
+        self.x.set_spring(spring);
+        self.y.set_spring(spring);
+
+        Ok(())
+    }
+
+    /// Choose how pan events are turned into a continuous gesture for tracking, Hermite unless
+    /// set. Strategies can be shared between scrollviews, and replacing one mid-gesture takes
+    /// effect on the next sample
+    pub fn set_pan_interpolation(&mut self, pan_interpolation: Arc<dyn PanInterpolation>) {
+        self.x.set_pan_interpolation(pan_interpolation.clone());
+        self.y.set_pan_interpolation(pan_interpolation);
+    }
+
+    /// The capabilities currently in effect for input to this scrollview
+    ///
+    /// These are the same on both axes, as set_source(), set_config() and set_capabilities()
+    /// always apply to both of them, so they are read from the horizontal one
+    pub fn capabilities(&self) -> Capabilities {
+        self.x.capabilities()
     }
 
     /// Set what device type is going to be providing any events that follow until the next source
     /// is declared
     pub fn set_source(&mut self, source: Source) {
+        // current_source is never Previous, so it is always the last concrete source
+        let source = match source {
+            Source::Previous => self.current_source,
+            source => source,
+        };
+
         self.current_source = source;
         self.x.set_source(source);
         self.y.set_source(source);
     }
 
-    fn update_config() {
-        let mut config_struct = CONFIG.write().expect("Couldn't lock config struct");
-
-        let mut config_dir: std::path::PathBuf = app_root(AppDataType::UserConfig, &APP_INFO).unwrap(); // TODO: gracefully handle
-        let config_file = std::path::PathBuf::from("config.ini");
-        config_dir.push(config_file);
-
-        let _ = Ini::from_file(&config_dir).map(|config| {
-            println!("Found config file, applying...");
-            config.get("config", "event_expiry_count").map(|v: usize| { config_struct.EVENT_EXPIRY_COUNT = v});
-            config.get("config", "sample_expiry_count").map(|v: usize| { config_struct.SAMPLE_EXPIRY_COUNT = v});
-
-            config.get("config", "ticks_to_coast").map(|v: f64| { config_struct.TICKS_TO_COAST = v});
-            config.get("config", "timestep").map(|v: f64| { config_struct.TIMESTEP = v});
-            config.get("config", "min_velocity_to_idle").map(|v: f64| { config_struct.MIN_VELOCITY_TO_IDLE = v});
-            config.get("config", "post_acceleration_scale_velocity").map(|v: f64| { config_struct.POST_ACCEL_SCALE_VELOCITY = v});
-            config.get("config", "pre_acceleration_scale_velocity").map(|v: f64| { config_struct.PRE_ACCEL_SCALE_VELOCITY = v});
-            config.get("config", "shift_window_ms").map(|v: f64| { config_struct.SHIFT_WINDOW_MS = v});
-            config.get("config", "overscroll_elasticity_coefficient").map(|v: f64| { config_struct.OVERSCROLL_ELASTICITY_COEFFICIENT = v});
-            config.get("config", "content_mass_value").map(|v: f64| { config_struct.CONTENT_MASS_VALUE = v});
-            config.get("config", "overscroll_spring_constant").map(|v: f64| { config_struct.OVERSCROLL_SPRING_CONSTANT = v});
-            config.get("config", "bounce_damping_factor").map(|v: f64| { config_struct.BOUNCE_DAMP_FACTOR = v});
-            config.get("config", "zero_delta_injection_wait_ms").map(|v: f64| { config_struct.MAX_MS_WITHOUT_ZERO_INJECTION = v});
-            config.get("config", "first_event_multiplier").map(|v: f64| { config_struct.MULTIPLY_FIRST_EVENT = v});
-            config.get("config", "accel_decel_descriminant").map(|v: f64| { config_struct.ACCEL_DECEL_DESCRIMINANT = v});
-            config.get("config", "acceleration_exponent").map(|v: f64| { config_struct.ACCELERATION_EXPONENT = v});
-            config.get("config", "fling_boost_constant_factor").map(|v: f64| { config_struct.FLING_BOOST_CONSTANT_FACTOR = v});
-
-            config.get("config", "flips_until_idle").map(|v: u64| { config_struct.FLIPS_TO_IDLE = v});
-        }).map_err(|_| {
-            println!("Couldn't find config file");
-            // maybe add message if can't be found?
-        });
+    /// The source events are currently assumed to come from
+    pub fn source(&self) -> Source {
+        self.current_source
+    }
+
+    /// Set the source for a device by its name, as chosen by the device match table of the
+    /// config (see Config::source_for_device()), or Source::Undefined if no entry matches.
+    /// Returns the source that was set
+    pub fn set_source_for_device(&mut self, device: &str) -> Source {
+        let source = self.config.source_for_device(device).unwrap_or(Source::Undefined);
+        self.set_source(source);
+
+        source
+    }
+}
+
+/// Unwraps a timestamp passed to one of the push_* functions, rejecting missing or zero timestamps
+fn timestamp_of(timestamp: Option<u64>) -> Result<Timestamp, Error> {
+    match timestamp {
+        None => Err(Error::MissingTimestamp),
+        Some(0) => Err(Error::ZeroTimestamp),
+        Some(timestamp) => Ok(timestamp),
+    }
+}
+
+fn duration_of(duration: f64) -> Result<f64, Error> {
+    if duration < 0.0 {
+        Err(Error::NegativeDuration(duration))
+    } else {
+        finite(duration)
+    }
+}
+
+fn finite(value: f64) -> Result<f64, Error> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(Error::NonFinite(value))
     }
 }
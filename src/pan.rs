@@ -0,0 +1,147 @@
+type Time = f64;
+type Position = f64;
+
+/// A single pan event as seen by a PanInterpolation, with the gesture's total offset so far
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct PanEvent {
+    /// Timestamp of the event in milliseconds
+    pub time: Time,
+    /// The current absolute "position" of the gesture, the sum of every delta up to this event
+    pub value: Position,
+}
+
+impl std::fmt::Display for PanEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}: {})", self.time, self.value)
+    }
+}
+
+/// Turns the pan events of a gesture into a continuous position, which the velocity of the
+/// content while tracking is measured from. See Scrollview::set_pan_interpolation()
+pub trait PanInterpolation: Send + Sync {
+    /// Position of the gesture at `time`
+    ///
+    /// `events` holds the most recent events of the gesture in time order, at least two of
+    /// them and none sharing a timestamp. `time` may fall before the first or after the last,
+    /// as sampling usually runs ahead of the latest event
+    fn position_at(&self, events: &[PanEvent], time: Time) -> Position;
+}
+
+/// Straight lines between consecutive events, the last two carried on past the end
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Linear;
+
+/// Like Linear, but averaged with the line through the pair of events before, which evens out
+/// jitter in the event timing at the cost of some lag
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AveragedLinear;
+
+/// Cubic Hermite spline through the events with Catmull-Rom tangents, so that the velocity
+/// changes smoothly between sparse events instead of jumping at each one. The default
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Hermite;
+
+/// Least squares line through the last `window` events up to the one at or after the
+/// sampled time, which smooths out noisy deltas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LeastSquares {
+    pub window: usize,
+}
+
+impl Default for LeastSquares {
+    fn default() -> Self { LeastSquares { window: 5 } }
+}
+
+impl PanInterpolation for Linear {
+    fn position_at(&self, events: &[PanEvent], time: Time) -> Position {
+        let (first, second) = segment(events, time);
+
+        sample_linear(first, second, time)
+    }
+}
+
+impl PanInterpolation for AveragedLinear {
+    fn position_at(&self, events: &[PanEvent], time: Time) -> Position {
+        let index = following(events, time);
+        let current = sample_linear(&events[index - 1], &events[index], time);
+
+        if index >= 2 {
+            (current + sample_linear(&events[index - 2], &events[index - 1], time)) / 2.0
+        } else {
+            current
+        }
+    }
+}
+
+impl PanInterpolation for Hermite {
+    fn position_at(&self, events: &[PanEvent], time: Time) -> Position {
+        let index = events.partition_point(|evt| evt.time < time);
+
+        if index == 0 || index == events.len() {
+            // nothing to curve towards outside the events, carry on in a straight line
+            let (first, second) = segment(events, time);
+            return sample_linear(first, second, time);
+        }
+
+        let (first, second) = (&events[index - 1], &events[index]);
+        let span = second.time - first.time;
+
+        let secant = slope_of(first, second);
+        let first_tangent = index.checked_sub(2).map(|i| slope_of(&events[i], second)).unwrap_or(secant);
+        let second_tangent = events.get(index + 1).map(|next| slope_of(first, next)).unwrap_or(secant);
+
+        let t = (time - first.time) / span;
+        let t2 = t * t;
+        let t3 = t2 * t;
+
+        (2.0 * t3 - 3.0 * t2 + 1.0) * first.value
+            + (t3 - 2.0 * t2 + t) * span * first_tangent
+            + (-2.0 * t3 + 3.0 * t2) * second.value
+            + (t3 - t2) * span * second_tangent
+    }
+}
+
+impl PanInterpolation for LeastSquares {
+    fn position_at(&self, events: &[PanEvent], time: Time) -> Position {
+        let end = following(events, time) + 1;
+        let window = &events[end.saturating_sub(self.window.max(2))..end];
+
+        let count = window.len() as f64;
+        let mean_time = window.iter().map(|evt| evt.time).sum::<f64>() / count;
+        let mean_value = window.iter().map(|evt| evt.value).sum::<f64>() / count;
+
+        let covariance: f64 = window.iter().map(|evt| (evt.time - mean_time) * (evt.value - mean_value)).sum();
+        let variance: f64 = window.iter().map(|evt| (evt.time - mean_time).powi(2)).sum();
+
+        let slope = if variance > 0.0 { covariance / variance } else { 0.0 };
+
+        mean_value + slope * (time - mean_time)
+    }
+}
+
+/// Index of the first event at or after `time`, kept within 1..len so that it always has an
+/// event before it
+fn following(events: &[PanEvent], time: Time) -> usize {
+    events.partition_point(|evt| evt.time < time).max(1).min(events.len() - 1)
+}
+
+/// The pair of events around `time`, or the first or last two if it is outside them
+fn segment(events: &[PanEvent], time: Time) -> (&PanEvent, &PanEvent) {
+    let index = following(events, time);
+
+    (&events[index - 1], &events[index])
+}
+
+fn sample_linear(first: &PanEvent, second: &PanEvent, sample: Time) -> Position {
+    let slope = slope_of(first, second);
+
+    slope * (sample - first.time) + first.value
+}
+
+fn slope_of(first: &PanEvent, second: &PanEvent) -> f64 {
+    if first.time == second.time {
+        0.0
+    } else {
+        (first.value - second.value) / (first.time - second.time)
+    }
+}
@@ -0,0 +1,90 @@
+type Position = f64;
+
+/// How strongly a fling is pulled onto a snap point
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SnapMode {
+    /// Every fling comes to rest on the snap point nearest to where it would have stopped
+    Mandatory,
+    /// Only flings that would have stopped within the given distance (in px) of a snap point
+    /// are pulled onto it, others come to rest naturally
+    Proximity(f64),
+}
+
+/// Which edge of the viewport a snap point is lined up with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SnapAlign {
+    /// The snap point ends up at the leading (top/left) edge of the viewport
+    Start,
+    /// The snap point ends up in the middle of the viewport
+    Center,
+    /// The snap point ends up at the trailing (bottom/right) edge of the viewport
+    End,
+}
+
+/// Where the snap points of an axis are, in content coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnapTargets {
+    /// An explicit list of points, such as the edges of carousel items of varying size
+    Points(Vec<f64>),
+    /// A point every given number of px starting at zero, such as evenly sized list rows
+    Interval(f64),
+}
+
+/// Snap configuration of a single axis, see Scrollview::set_snap_points()
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapPoints {
+    pub targets: SnapTargets,
+    pub mode: SnapMode,
+    pub align: SnapAlign,
+}
+
+impl SnapPoints {
+    pub fn points(points: Vec<f64>, mode: SnapMode, align: SnapAlign) -> SnapPoints {
+        SnapPoints { targets: SnapTargets::Points(points), mode, align }
+    }
+
+    pub fn interval(interval: f64, mode: SnapMode, align: SnapAlign) -> SnapPoints {
+        SnapPoints { targets: SnapTargets::Interval(interval), mode, align }
+    }
+
+    /// Distance between a snap point and the scroll position that lines it up with the viewport
+    fn align_offset(&self, viewport: f64) -> f64 {
+        match self.align {
+            SnapAlign::Start => 0.0,
+            SnapAlign::Center => viewport / 2.0,
+            SnapAlign::End => viewport,
+        }
+    }
+
+    /// Picks the scroll position a fling that would naturally stop at `rest` should stop at
+    /// instead, or None if it should be left alone
+    ///
+    /// Snapped positions are clamped to the track between lower and upper, so points
+    /// that can't be reached still pull the content as far as it can go
+    pub(crate) fn select(&self, rest: Position, viewport: f64, lower: Position, upper: Position) -> Option<Position> {
+        let offset = self.align_offset(viewport);
+        let clamp = |position: Position| position.min(upper).max(lower);
+
+        let nearest = match &self.targets {
+            SnapTargets::Points(points) => points
+                .iter()
+                .filter(|point| point.is_finite())
+                .map(|point| clamp(point - offset))
+                .min_by(|a, b| {
+                    (a - rest).abs().partial_cmp(&(b - rest).abs()).unwrap_or(std::cmp::Ordering::Equal)
+                }),
+            SnapTargets::Interval(interval) if *interval > 0.0 && interval.is_finite() => {
+                let index = ((rest + offset) / interval).round();
+
+                Some(clamp(index * interval - offset))
+            },
+            SnapTargets::Interval(_) => None,
+        }?;
+
+        match self.mode {
+            SnapMode::Mandatory => Some(nearest),
+            SnapMode::Proximity(threshold) if (nearest - rest).abs() <= threshold => Some(nearest),
+            SnapMode::Proximity(_) => None,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+/*!
+ * The damped spring that pulls overscrolled content back to the edge of the track
+ *
+ * Solved exactly like the Deceleration models, so a bounce evaluates in constant time however
+ * long it has been since it was last sampled, and feels the same whatever TIMESTEP is set to
+ */
+
+use std::ops::Bound;
+
+use crate::{AllowedRange, Diagnostic, DiagnosticKind, Error};
+
+type Velocity = f64; // px/ms
+type TimeDelta = f64; // ms
+type Position = f64; // px
+
+// Damping ratios this close to 1 are treated as critically damped, where the under and over
+// damped solutions both divide by zero
+const CRITICAL_TOLERANCE: f64 = 1e-6;
+
+// Without stiffness nothing pulls the content back, and without damping it never settles
+const POSITIVE: AllowedRange = AllowedRange { min: Bound::Excluded(0.0), max: Bound::Unbounded };
+
+/// A damped spring between overscrolled content and the edge of the track, see Config::spring()
+///
+/// At a damping_ratio of 1 or more the content comes back to the edge without ever passing it,
+/// below 1 it swings back past the edge and oscillates around it a few times before settling
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spring {
+    /// Acceleration back towards the edge in px/ms² for every px of overscroll
+    pub stiffness: f64,
+    /// 1 is critically damped, the quickest return that doesn't overshoot the edge
+    pub damping_ratio: f64,
+}
+
+impl Spring {
+    /// A spring that, undamped, would swing back and forth once every `response` ms. Shorter
+    /// responses are stiffer
+    ///
+    /// Returns Error::InvalidConfig if the response isn't above 0 or the spring it gives
+    /// isn't valid, see validate()
+    pub fn with_response(response: TimeDelta, damping_ratio: f64) -> Result<Spring, Error> {
+        if !POSITIVE.contains(response) {
+            return Err(Error::InvalidConfig(vec![out_of_range("response", response)]));
+        }
+
+        let frequency = 2.0 * std::f64::consts::PI / response;
+        let spring = Spring { stiffness: frequency * frequency, damping_ratio };
+        spring.validate()?;
+
+        Ok(spring)
+    }
+
+    /// Checks that the spring brings overscrolled content back to rest, which takes a stiffness
+    /// and damping ratio that are both finite and above 0. Each one that isn't is listed in the
+    /// Error::InvalidConfig under the key that configures it
+    pub fn validate(&self) -> Result<(), Error> {
+        let diagnostics: Vec<Diagnostic> = [("spring_stiffness", self.stiffness), ("spring_damping_ratio", self.damping_ratio)]
+            .iter()
+            .filter(|&&(_, value)| !POSITIVE.contains(value))
+            .map(|&(key, value)| out_of_range(key, value))
+            .collect();
+
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidConfig(diagnostics))
+        }
+    }
+
+    /// Undamped angular frequency in radians/ms
+    pub fn frequency(&self) -> f64 {
+        self.stiffness.sqrt()
+    }
+
+    /// Whether the content swings back past the edge before settling on it
+    pub fn overshoots(&self) -> bool {
+        self.damping_ratio < 1.0 - CRITICAL_TOLERANCE
+    }
+
+    /// Displacement from the edge and velocity `elapsed` ms after starting from the given ones
+    pub fn advance(&self, displacement: Position, velocity: Velocity, elapsed: TimeDelta) -> (Position, Velocity) {
+        let (w, z, t) = (self.frequency(), self.damping_ratio, elapsed);
+
+        if w.is_nan() || w <= 0.0 {
+            // no spring at all, nothing pulls the content back
+            return (displacement + velocity * t, velocity);
+        }
+
+        if (z - 1.0).abs() < CRITICAL_TOLERANCE {
+            let decay = (-w * t).exp();
+            let rate = velocity + w * displacement;
+
+            (
+                (displacement + rate * t) * decay,
+                (velocity - w * rate * t) * decay,
+            )
+        } else if z < 1.0 {
+            let damped = w * (1.0 - z * z).sqrt();
+            let decay = (-z * w * t).exp();
+            let (sin, cos) = (damped * t).sin_cos();
+
+            (
+                decay * (displacement * cos + (velocity + z * w * displacement) / damped * sin),
+                decay * (velocity * cos - (z * w * velocity + w * w * displacement) / damped * sin),
+            )
+        } else {
+            let root = (z * z - 1.0).sqrt();
+            let (fast, slow) = (-w * (z + root), -w * (z - root));
+            let first = (velocity - slow * displacement) / (fast - slow);
+            let second = displacement - first;
+
+            (
+                first * (fast * t).exp() + second * (slow * t).exp(),
+                first * fast * (fast * t).exp() + second * slow * (slow * t).exp(),
+            )
+        }
+    }
+}
+
+fn out_of_range(key: &str, value: f64) -> Diagnostic {
+    Diagnostic { line: None, key: key.to_owned(), kind: DiagnosticKind::OutOfRange { value, allowed: POSITIVE } }
+}
@@ -0,0 +1,119 @@
+extern crate libscroll;
+
+use libscroll::{Axis, AxisVector, Config, Easing, Error, ScrollPhase, Scrollview, Source};
+
+/// A touchscreen scrollview with 1500px to scroll through along y, idle at the top
+fn scrollview() -> Scrollview {
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(2000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+
+    scrollview
+}
+
+#[test]
+fn scrolls_arrive_at_their_target_after_the_duration() {
+    let mut scrollview = scrollview();
+    scrollview.scroll_to(AxisVector::new(0.0, 600.0), Some(100), 200.0, Easing::EaseInOut).unwrap();
+
+    assert_eq!(scrollview.phase(Axis::Vertical), ScrollPhase::Animating);
+    assert!(scrollview.sample(100).y.abs() < 1e-9);
+
+    let halfway = scrollview.sample(200).y;
+    assert!((halfway - 300.0).abs() < 1e-6, "symmetric curve was at {} halfway", halfway);
+    assert!(scrollview.animating());
+
+    assert_eq!(scrollview.sample(300).y, 600.0);
+    assert_eq!(scrollview.velocity(300).y, 0.0);
+    assert!(!scrollview.animating());
+}
+
+#[test]
+fn targets_are_clamped_to_the_track() {
+    let mut scrollview = scrollview();
+    scrollview.scroll_to(AxisVector::new(0.0, 5000.0), Some(100), 100.0, Easing::Linear).unwrap();
+    assert_eq!(scrollview.sample(200).y, 1500.0);
+
+    scrollview.scroll_by(AxisVector::new(0.0, -5000.0), Some(300), 100.0, Easing::Linear).unwrap();
+    assert_eq!(scrollview.sample(400).y, 0.0);
+}
+
+#[test]
+fn repeated_scroll_by_adds_up() {
+    let mut scrollview = scrollview();
+    scrollview.scroll_by(AxisVector::new(0.0, 100.0), Some(100), 200.0, Easing::Linear).unwrap();
+    scrollview.sample(150);
+    scrollview.scroll_by(AxisVector::new(0.0, 100.0), Some(150), 200.0, Easing::Linear).unwrap();
+
+    assert_eq!(scrollview.sample(350).y, 200.0);
+}
+
+#[test]
+fn easing_shapes_the_progress() {
+    for &easing in &[Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut, Easing::CubicBezier(0.2, 0.8, 0.4, 1.0)] {
+        assert!(easing.progress(0.0).abs() < 1e-9, "{:?} doesn't start at 0", easing);
+        assert!((easing.progress(1.0) - 1.0).abs() < 1e-9, "{:?} doesn't end at 1", easing);
+    }
+
+    assert!((Easing::Linear.progress(0.25) - 0.25).abs() < 1e-9);
+    assert!(Easing::EaseIn.progress(0.25) < 0.25);
+    assert!(Easing::EaseOut.progress(0.25) > 0.25);
+    assert!(Easing::EaseIn.slope(0.0) < Easing::EaseIn.slope(1.0));
+    assert_eq!(Easing::default(), Easing::EaseInOut);
+
+    let mut linear = scrollview();
+    linear.scroll_to(AxisVector::new(0.0, 400.0), Some(100), 400.0, Easing::Linear).unwrap();
+    assert!((linear.sample(200).y - 100.0).abs() < 1e-6);
+    assert!((linear.velocity(200).y - 1.0).abs() < 1e-6);
+
+    let mut eased = scrollview();
+    eased.scroll_to(AxisVector::new(0.0, 400.0), Some(100), 400.0, Easing::EaseIn).unwrap();
+    assert!(eased.sample(200).y < 100.0);
+}
+
+#[test]
+fn input_cancels_the_animation_where_it_is() {
+    let mut scrollview = scrollview();
+    scrollview.scroll_to(AxisVector::new(0.0, 600.0), Some(100), 200.0, Easing::Linear).unwrap();
+    let midway = scrollview.sample(200).y;
+
+    scrollview.push_interrupt(Some(200)).unwrap();
+    assert_ne!(scrollview.phase(Axis::Vertical), ScrollPhase::Animating);
+    assert!(!scrollview.animating());
+    // stops within a step of where it was interrupted, and stays there
+    let stopped = scrollview.sample(300).y;
+    assert!((stopped - midway).abs() < 1.0, "kept going to {} after being interrupted at {}", stopped, midway);
+    assert_eq!(scrollview.sample(400).y, stopped);
+
+    // a pan takes over from the current position too, moving the content just as it would from rest
+    let pan = |scrollview: &mut Scrollview, from: u64| {
+        let start = scrollview.sample(from).y;
+        for i in 1..=5 {
+            scrollview.push_pan(Axis::Vertical, 10.0, Some(from + i * 8)).unwrap();
+            scrollview.sample(from + i * 8 + 4);
+        }
+        assert_eq!(scrollview.phase(Axis::Vertical), ScrollPhase::Tracking);
+
+        scrollview.sample(from + 48).y - start
+    };
+
+    scrollview.scroll_to(AxisVector::new(0.0, 0.0), Some(500), 200.0, Easing::Linear).unwrap();
+    scrollview.sample(600);
+    let (animated, at_rest) = (pan(&mut scrollview, 600), pan(&mut self::scrollview(), 600));
+    assert!((animated - at_rest).abs() < 1.0, "moved {} after the animation but {} from rest", animated, at_rest);
+}
+
+#[test]
+fn bad_arguments_are_rejected() {
+    let mut scrollview = scrollview();
+
+    assert!(matches!(
+        scrollview.scroll_to(AxisVector::new(0.0, f64::NAN), Some(100), 200.0, Easing::Linear),
+        Err(Error::NonFinite(_))
+    ));
+    assert!(matches!(
+        scrollview.scroll_by(AxisVector::new(0.0, 10.0), Some(100), -1.0, Easing::Linear),
+        Err(Error::NegativeDuration(_))
+    ));
+    assert!(!scrollview.animating());
+}
@@ -0,0 +1,95 @@
+extern crate libscroll;
+
+use libscroll::{Axis, Capabilities, Config, Scrollview, Source};
+
+const FRAME_MS: u64 = 16;
+
+fn scrollview(source: Source, config: Config) -> Scrollview {
+    let mut scrollview = Scrollview::with_config(config).unwrap();
+    scrollview.set_geometry(2000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(source);
+    scrollview
+}
+
+/// Pans along y by `dy` per event, 8ms apart, sampling every other event. Returns the
+/// timestamp of the last sample
+fn pan(scrollview: &mut Scrollview, dy: f64, count: u64) -> u64 {
+    scrollview.push_interrupt(Some(1)).unwrap();
+
+    for i in 1..=count {
+        scrollview.push_pan(Axis::Vertical, dy, Some(i * 8)).unwrap();
+        if i % 2 == 0 {
+            scrollview.sample(i * 8 + 4);
+        }
+    }
+
+    count * 8 + 4
+}
+
+#[test]
+fn builtin_capabilities_follow_the_source() {
+    let wheel = scrollview(Source::Mousewheel, Config::default());
+    assert_eq!(wheel.capabilities(), Capabilities { overscrolls: false, kinetic: false, accelerates: false });
+
+    let touchpad = scrollview(Source::Touchpad, Config::default());
+    assert_eq!(touchpad.capabilities(), Capabilities { overscrolls: true, kinetic: true, accelerates: true });
+}
+
+#[test]
+fn capabilities_can_be_configured_per_source() {
+    let config = Config::from_ini_str("[mousewheel]\noverscrolls = true\n[touchscreen]\nkinetic = no\n").unwrap();
+
+    let wheel = scrollview(Source::Mousewheel, config.clone());
+    assert!(wheel.capabilities().overscrolls);
+    assert!(!wheel.capabilities().kinetic);
+
+    let mut touchscreen = scrollview(Source::Touchscreen, config);
+    assert!(!touchscreen.capabilities().kinetic);
+    assert!(touchscreen.capabilities().overscrolls);
+
+    // a non kinetic release stops where it was let go
+    let last = pan(&mut touchscreen, 10.0, 20);
+    let released = touchscreen.sample(last);
+    touchscreen.push_fling(Some(last)).unwrap();
+
+    let mut position = released;
+    for frame in 1..100 {
+        position = touchscreen.sample(last + frame * FRAME_MS);
+        if !touchscreen.animating() {
+            break;
+        }
+    }
+
+    assert!(!touchscreen.animating());
+    assert!(released.y > 0.0);
+    assert_eq!(position.y, released.y);
+}
+
+#[test]
+fn forced_capabilities_override_the_source() {
+    let mut wheel = scrollview(Source::Mousewheel, Config::default());
+    wheel.set_capabilities(Some(Capabilities { overscrolls: true, kinetic: true, accelerates: false }));
+
+    let last = pan(&mut wheel, -20.0, 20);
+    assert!(wheel.sample(last).y < 0.0, "forced overscroll should let the wheel pull past the edge");
+
+    wheel.set_capabilities(None);
+    assert_eq!(wheel.capabilities(), Capabilities::of(Source::Mousewheel));
+}
+
+#[test]
+fn previous_source_resolves_to_the_last_concrete_one() {
+    let mut scrollview = scrollview(Source::Previous, Config::default());
+    assert_eq!(scrollview.source(), Source::Undefined);
+
+    scrollview.set_source(Source::Touchpad);
+    scrollview.set_source(Source::Previous);
+    assert_eq!(scrollview.source(), Source::Touchpad);
+    assert_eq!(scrollview.capabilities(), Capabilities::of(Source::Touchpad));
+
+    scrollview.set_source(Source::Mousewheel);
+    scrollview.set_source(Source::Previous);
+    scrollview.set_source(Source::Previous);
+    assert_eq!(scrollview.source(), Source::Mousewheel);
+    assert_eq!(scrollview.capabilities(), Capabilities::of(Source::Mousewheel));
+}
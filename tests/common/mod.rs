@@ -0,0 +1,62 @@
+//! Gestures shared by the integration tests
+
+// each test crate only uses some of these
+#![allow(dead_code)]
+
+use libscroll::{Axis, AxisVector, Scrollview};
+
+/// Time between samples of a 60fps render loop
+pub const FRAME_MS: u64 = 16;
+
+/// Pans along y by each of `deltas` in turn from the top, one event every 8ms, sampling 4ms
+/// after each like a render loop would. Returns the time of the last sample
+pub fn pan(scrollview: &mut Scrollview, deltas: &[f64]) -> u64 {
+    scrollview.push_interrupt(Some(1)).unwrap();
+    for (i, &delta) in deltas.iter().enumerate() {
+        let timestamp = (i as u64 + 1) * 8;
+        scrollview.push_pan(Axis::Vertical, delta, Some(timestamp)).unwrap();
+        scrollview.sample(timestamp + 4);
+    }
+
+    deltas.len() as u64 * 8 + 4
+}
+
+/// Pans like pan() and releases at the time of the last sample, which is returned
+pub fn fling(scrollview: &mut Scrollview, deltas: &[f64]) -> u64 {
+    let release = pan(scrollview, deltas);
+    scrollview.push_fling(Some(release)).unwrap();
+
+    release
+}
+
+/// Pushes a diagonal pan gesture of `count` events 8ms apart, sampling every other event
+/// like a 60fps render loop would. Returns the timestamp of the last event
+pub fn pan_diagonal(scrollview: &mut Scrollview, dx: f64, dy: f64, count: u64) -> u64 {
+    scrollview.push_interrupt(Some(1)).unwrap();
+
+    let mut timestamp = 1;
+    for i in 1..=count {
+        timestamp = i * 8;
+        scrollview.push_pan(Axis::Horizontal, dx, Some(timestamp)).unwrap();
+        scrollview.push_pan(Axis::Vertical, dy, Some(timestamp)).unwrap();
+
+        if i % 2 == 0 {
+            scrollview.sample(timestamp + 4);
+        }
+    }
+
+    timestamp
+}
+
+/// Samples once per frame until the scrollview stops animating, returning the final position
+pub fn settle(scrollview: &mut Scrollview, from: u64) -> AxisVector<f64> {
+    let mut position = scrollview.sample(from);
+    for frame in 1..1000 {
+        position = scrollview.sample(from + frame * FRAME_MS);
+        if !scrollview.animating() {
+            return position;
+        }
+    }
+
+    panic!("scrollview never stopped animating, last position {}", position);
+}
@@ -0,0 +1,478 @@
+extern crate libscroll;
+
+mod common;
+
+use std::sync::Mutex;
+
+use libscroll::{
+    Axis, Config, ConfigLocation, ConfigWatcher, Diagnostic, DiagnosticKind, Error, Layer, LayeredConfig, Overrides, Scrollview,
+    Source,
+};
+
+// tests that touch the global config can't run alongside each other
+static GLOBAL: Mutex<()> = Mutex::new(());
+
+#[test]
+fn ini_overrides_only_given_keys() {
+    let config = Config::from_ini_str("[config]\nspring_stiffness = 0.002\nflips_until_idle = 5\n").unwrap();
+    let defaults = Config::default();
+
+    assert_eq!(config.SPRING_STIFFNESS, 0.002);
+    assert_eq!(config.FLIPS_TO_IDLE, 5);
+    assert_eq!(config.TIMESTEP, defaults.TIMESTEP);
+    assert_eq!(config.SPRING_DAMPING_RATIO, defaults.SPRING_DAMPING_RATIO);
+}
+
+#[test]
+fn load_from_path_reads_file() {
+    let path = std::env::temp_dir().join(format!("libscroll-config-test-{}.ini", std::process::id()));
+    std::fs::write(&path, "[config]\npage_skip_velocity = 2.5\n").unwrap();
+
+    let config = Config::load_from_path(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(config.unwrap().PAGE_SKIP_VELOCITY, 2.5);
+}
+
+#[test]
+fn load_from_missing_path_is_an_error() {
+    let path = std::env::temp_dir().join("libscroll-config-test-does-not-exist.ini");
+
+    match Config::load_from_path(&path) {
+        Err(Error::ConfigUnreadable(p, _)) => assert_eq!(p, path),
+        other => panic!("expected ConfigUnreadable, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn failed_loads_of_the_global_config_are_kept() {
+    let _global = GLOBAL.lock().unwrap();
+    let path = std::env::temp_dir().join(format!("libscroll-load-error-test-{}.ini", std::process::id()));
+    std::fs::write(&path, "[config]\nspring_stiffness = 0.001\n").unwrap();
+    libscroll::set_config_location(ConfigLocation::Path(path.clone()));
+    libscroll::reload_config().unwrap();
+    assert_eq!(libscroll::last_load_error(), None);
+
+    std::fs::remove_file(&path).unwrap();
+    let error = libscroll::reload_config().unwrap_err();
+    assert!(matches!(&error, Error::ConfigUnreadable(p, _) if *p == path));
+    assert_eq!(libscroll::last_load_error(), Some(error));
+    // the config that was loaded before is kept
+    assert_eq!(Scrollview::new().config().SPRING_STIFFNESS, 0.001);
+
+    libscroll::set_config_location(ConfigLocation::Disabled);
+    libscroll::reload_config().unwrap();
+    assert_eq!(libscroll::last_load_error(), None);
+}
+
+#[test]
+fn watcher_swaps_changed_config_into_running_scrollviews() {
+    let _global = GLOBAL.lock().unwrap();
+    let path = std::env::temp_dir().join(format!("libscroll-watch-test-{}.ini", std::process::id()));
+    std::fs::write(&path, "[config]\nspring_stiffness = 0.001\n").unwrap();
+    libscroll::set_config_location(ConfigLocation::Path(path.clone()));
+    libscroll::reload_config().unwrap();
+
+    let mut following = Scrollview::new();
+    let mut own = Scrollview::with_config(Config::default()).unwrap();
+    let mut watcher = ConfigWatcher::new();
+    assert_eq!(following.config().SPRING_STIFFNESS, 0.001);
+    assert!(!watcher.poll().unwrap());
+
+    std::fs::write(&path, "[config]\nspring_stiffness = 0.0015\nflips_until_idle = 3\n").unwrap();
+    let reloaded = watcher.poll();
+    std::fs::remove_file(&path).unwrap();
+    libscroll::set_config_location(ConfigLocation::Disabled);
+
+    assert!(reloaded.unwrap());
+    // not swapped in until the next frame
+    assert_eq!(following.config().SPRING_STIFFNESS, 0.001);
+
+    following.sample(16);
+    own.sample(16);
+    assert_eq!(following.config().SPRING_STIFFNESS, 0.0015);
+    assert_eq!(following.config().FLIPS_TO_IDLE, 3);
+    assert_eq!(own.config().SPRING_STIFFNESS, Config::default().SPRING_STIFFNESS);
+}
+
+#[test]
+fn reloads_keep_flings_in_progress_continuous() {
+    let _global = GLOBAL.lock().unwrap();
+    libscroll::set_config_location(ConfigLocation::Disabled);
+    libscroll::reload_config().unwrap();
+
+    let mut scrollview = Scrollview::new();
+    scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+    let release = common::fling(&mut scrollview, &[40.0; 10]);
+
+    let (mut before, mut after) = (0.0f64, 0.0f64);
+    let mut last = scrollview.sample(release).y;
+    for frame in 1..=12 {
+        let position = scrollview.sample(release + frame * 8).y;
+        before = before.max((position - last).abs());
+        last = position;
+    }
+
+    // swapped in on the next sample, partway through the fling
+    libscroll::set_runtime_override("friction_coefficient", 0.00002).unwrap();
+    for frame in 13..=24 {
+        let position = scrollview.sample(release + frame * 8).y;
+        after = after.max((position - last).abs());
+        last = position;
+    }
+    libscroll::clear_runtime_override("friction_coefficient");
+
+    assert!(before > 0.0);
+    assert!(after <= before, "moved {}px in a frame after the reload, at most {}px before", after, before);
+    assert!(scrollview.animating());
+}
+
+fn diagnostics_of(contents: &str) -> Vec<Diagnostic> {
+    match Config::from_ini_str(contents) {
+        Err(Error::InvalidConfig(diagnostics)) => diagnostics,
+        other => panic!("expected InvalidConfig, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn invalid_configs_are_refused_with_every_problem_listed() {
+    let diagnostics = diagnostics_of(
+        "[config]\n\
+         timstep = 0.1\n\
+         flips_until_idle = 2.5\n\
+         timestep = 0\n\
+         spring_stiffness = -0.001\n\
+         some garbage\n",
+    );
+
+    let summary: Vec<_> = diagnostics.iter().map(|d| (d.line, d.key.as_str())).collect();
+    assert_eq!(summary, vec![
+        (Some(2), "timstep"),
+        (Some(3), "flips_until_idle"),
+        (Some(4), "timestep"),
+        (Some(5), "spring_stiffness"),
+        (Some(6), ""),
+    ]);
+
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::UnknownKey { suggestion: Some("timestep") });
+    assert!(matches!(diagnostics[1].kind, DiagnosticKind::TypeError { .. }));
+    assert!(matches!(diagnostics[2].kind, DiagnosticKind::OutOfRange { value, .. } if value == 0.0));
+    assert_eq!(diagnostics[2].to_string(), "line 4: `timestep` is 0, which is outside of the allowed range (0, 10]");
+    assert!(matches!(diagnostics[4].kind, DiagnosticKind::Malformed(_)));
+}
+
+#[test]
+fn keys_outside_the_config_section_are_reported() {
+    let diagnostics = diagnostics_of("timestep = 0.1\n[confg]\ntimestep = 0.2\n");
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::UnknownSection(String::new()));
+    assert_eq!(diagnostics[1].kind, DiagnosticKind::UnknownSection("confg".to_owned()));
+}
+
+#[test]
+fn scrollviews_fling_by_their_own_config() {
+    let slippery = Config { FRICTION_COEFFICIENT: Config::default().FRICTION_COEFFICIENT / 4.0, ..Config::default() };
+
+    // both exist side by side, so neither config can have replaced the other
+    let mut scrollviews: Vec<Scrollview> = vec![Config::default(), slippery].into_iter().map(|config| {
+        let mut scrollview = Scrollview::with_config(config).unwrap();
+        scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+        scrollview.set_source(Source::Touchscreen);
+        scrollview
+    }).collect();
+
+    let rests: Vec<f64> = scrollviews.iter_mut().map(|scrollview| {
+        let mut time = common::fling(scrollview, &[20.0; 10]);
+        while scrollview.animating() {
+            time += 16;
+            scrollview.sample(time);
+        }
+
+        scrollview.sample(time).y
+    }).collect();
+
+    // the same gesture goes further with less friction
+    assert!(rests[1] > rests[0] * 1.5, "flung to {} with less friction but {} by default", rests[1], rests[0]);
+    assert_eq!(scrollviews[0].config().FRICTION_COEFFICIENT, Config::default().FRICTION_COEFFICIENT);
+}
+
+#[test]
+fn set_config_refuses_out_of_range_values() {
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    let config = Config { SPRING_DAMPING_RATIO: -1.0, ..Config::default() };
+
+    assert!(scrollview.set_config(config).is_err());
+    assert_eq!(scrollview.config().SPRING_DAMPING_RATIO, Config::default().SPRING_DAMPING_RATIO);
+    assert!(Config::default().diagnostics().is_empty());
+}
+
+#[test]
+fn with_config_refuses_out_of_range_values() {
+    // stepping by a TIMESTEP of 0 would never get anywhere
+    let config = Config { TIMESTEP: 0.0, SPRING_DAMPING_RATIO: -1.0, FLIPS_TO_IDLE: 5, ..Config::default() };
+
+    let diagnostics = match Scrollview::with_config(config.clone()) {
+        Err(Error::InvalidConfig(diagnostics)) => diagnostics,
+        other => panic!("expected diagnostics, got {:?}", other.map(|scrollview| scrollview.config().clone())),
+    };
+    assert_eq!(diagnostics, config.diagnostics());
+    assert_eq!(diagnostics.iter().map(|diagnostic| diagnostic.key.as_str()).collect::<Vec<_>>(), ["timestep", "spring_damping_ratio"]);
+
+    assert!(Scrollview::with_config(Config { FLIPS_TO_IDLE: 5, ..Config::default() }).is_ok());
+}
+
+#[test]
+fn higher_layers_take_precedence() {
+    let mut system = Overrides::new();
+    system.set("spring_stiffness", 0.001).unwrap();
+    system.set("timestep", 0.2).unwrap();
+    let mut user = Overrides::new();
+    user.set("spring_stiffness", 0.0012).unwrap();
+
+    let mut layers = LayeredConfig::new();
+    layers.set_layer(Layer::User, user);
+    layers.set_layer(Layer::System, system);
+    layers.layer_mut(Layer::Runtime).set("flips_until_idle", 4.0).unwrap();
+
+    let config = layers.resolve();
+    assert_eq!(config.SPRING_STIFFNESS, 0.0012);
+    assert_eq!(config.TIMESTEP, 0.2);
+    assert_eq!(config.FLIPS_TO_IDLE, 4);
+
+    assert_eq!(layers.source_of("spring_stiffness"), Some(Layer::User));
+    assert_eq!(layers.source_of("timestep"), Some(Layer::System));
+    assert_eq!(layers.source_of("flips_until_idle"), Some(Layer::Runtime));
+    assert_eq!(layers.source_of("spring_damping_ratio"), Some(Layer::Default));
+    assert_eq!(layers.source_of("no_such_key"), None);
+
+    assert!(Overrides::new().set("flips_until_idle", 1.5).is_err());
+    assert!(Overrides::new().set("timestep", -1.0).is_err());
+}
+
+#[test]
+fn application_sections_and_runtime_overrides_apply_to_the_global_config() {
+    let _global = GLOBAL.lock().unwrap();
+
+    let path = std::env::temp_dir().join(format!("libscroll-layers-test-{}.ini", std::process::id()));
+    std::fs::write(&path, "[config]\n\
+        spring_stiffness = 0.001\n\
+        timestep = 0.2\n\
+        [app.viewer]\n\
+        spring_stiffness = 0.0014\n\
+        [app.editor]\n\
+        timestep = 0.05\n").unwrap();
+
+    libscroll::set_config_location(ConfigLocation::Path(path.clone()));
+    libscroll::set_application_name(Some("viewer"));
+    let reloaded = libscroll::reload_config();
+    std::fs::remove_file(&path).unwrap();
+    reloaded.unwrap();
+
+    libscroll::set_runtime_override("flips_until_idle", 7.0).unwrap();
+    let config = Scrollview::new().config().clone();
+
+    assert_eq!(config.SPRING_STIFFNESS, 0.0014);
+    assert_eq!(config.TIMESTEP, 0.2);
+    assert_eq!(config.FLIPS_TO_IDLE, 7);
+    assert_eq!(libscroll::config_source("spring_stiffness"), Some(Layer::Application));
+    assert_eq!(libscroll::config_source("timestep"), Some(Layer::User));
+    assert_eq!(libscroll::config_source("flips_until_idle"), Some(Layer::Runtime));
+    assert_eq!(libscroll::config_source("spring_damping_ratio"), Some(Layer::Default));
+
+    // runtime overrides survive reloads, and go away once cleared
+    libscroll::set_config_location(ConfigLocation::Disabled);
+    libscroll::set_application_name(None);
+    libscroll::reload_config().unwrap();
+    assert_eq!(libscroll::config_source("flips_until_idle"), Some(Layer::Runtime));
+    libscroll::clear_runtime_override("flips_until_idle");
+    assert_eq!(libscroll::config_source("flips_until_idle"), Some(Layer::Default));
+}
+
+/// Points the system and user config directories at fresh temporary ones holding the given
+/// config.ini files, and LIBSCROLL_CONFIG at `env` if given, for the duration of `test`
+fn with_config_dirs(name: &str, system: &str, user: &str, env: Option<&str>, test: impl FnOnce()) {
+    let root = std::env::temp_dir().join(format!("libscroll-{}-{}", name, std::process::id()));
+    for (dir, contents) in &[("system/libscroll", system), ("user/libscroll", user)] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+        std::fs::write(root.join(dir).join("config.ini"), contents).unwrap();
+    }
+
+    std::env::set_var("XDG_CONFIG_DIRS", root.join("system"));
+    std::env::set_var("XDG_CONFIG_HOME", root.join("user"));
+    match env {
+        Some(env) => std::env::set_var(libscroll::CONFIG_ENV, env),
+        None => std::env::remove_var(libscroll::CONFIG_ENV),
+    }
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(test));
+
+    std::env::remove_var("XDG_CONFIG_DIRS");
+    std::env::remove_var("XDG_CONFIG_HOME");
+    std::env::remove_var(libscroll::CONFIG_ENV);
+    libscroll::set_config_location(ConfigLocation::Disabled);
+    libscroll::reload_config().unwrap();
+    std::fs::remove_dir_all(&root).unwrap();
+
+    if let Err(panic) = result {
+        std::panic::resume_unwind(panic);
+    }
+}
+
+#[test]
+fn default_location_reads_the_system_and_user_files() {
+    let _global = GLOBAL.lock().unwrap();
+
+    with_config_dirs("default-location", "[config]\ntimestep = 0.2\n", "[config]\nspring_stiffness = 0.001\n", None, || {
+        libscroll::set_config_location(ConfigLocation::Default);
+        libscroll::reload_config().unwrap();
+
+        assert_eq!(libscroll::config_source("timestep"), Some(Layer::System));
+        assert_eq!(libscroll::config_source("spring_stiffness"), Some(Layer::User));
+        assert_eq!(Scrollview::new().config().SPRING_STIFFNESS, 0.001);
+    });
+}
+
+#[test]
+fn config_env_replaces_the_user_file() {
+    let _global = GLOBAL.lock().unwrap();
+    let path = std::env::temp_dir().join(format!("libscroll-env-test-{}.ini", std::process::id()));
+    std::fs::write(&path, "[config]\nspring_stiffness = 0.002\n").unwrap();
+
+    with_config_dirs("env-location", "[config]\ntimestep = 0.2\n", "[config]\nspring_stiffness = 0.001\n", path.to_str(), || {
+        libscroll::set_config_location(ConfigLocation::Default);
+        libscroll::reload_config().unwrap();
+
+        assert_eq!(libscroll::config_source("spring_stiffness"), Some(Layer::User));
+        assert_eq!(Scrollview::new().config().SPRING_STIFFNESS, 0.002);
+        assert_eq!(Scrollview::new().config().TIMESTEP, 0.2);
+
+        // unlike the usual user file, one named explicitly has to exist
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(libscroll::reload_config(), Err(Error::ConfigUnreadable(..))));
+        assert_eq!(Scrollview::new().config().SPRING_STIFFNESS, 0.002);
+    });
+}
+
+#[test]
+fn empty_config_env_reads_no_files() {
+    let _global = GLOBAL.lock().unwrap();
+
+    with_config_dirs("empty-env", "[config]\ntimestep = 0.2\n", "[config]\nspring_stiffness = 0.001\n", Some(""), || {
+        libscroll::set_config_location(ConfigLocation::Default);
+        libscroll::reload_config().unwrap();
+
+        assert_eq!(libscroll::config_source("spring_stiffness"), Some(Layer::Default));
+        assert_eq!(libscroll::config_source("timestep"), Some(Layer::Default));
+        assert_eq!(Scrollview::new().config().SPRING_STIFFNESS, Config::default().SPRING_STIFFNESS);
+        assert_eq!(Scrollview::new().config().TIMESTEP, Config::default().TIMESTEP);
+
+        // and nothing for a watcher to look at either
+        let mut watcher = ConfigWatcher::new();
+        assert!(!watcher.poll().unwrap());
+    });
+}
+
+#[test]
+fn disabled_location_reads_no_files() {
+    let _global = GLOBAL.lock().unwrap();
+    let path = std::env::temp_dir().join(format!("libscroll-disabled-test-{}.ini", std::process::id()));
+    std::fs::write(&path, "[config]\nspring_stiffness = 0.002\n").unwrap();
+
+    with_config_dirs("disabled", "[config]\ntimestep = 0.2\n", "[config]\nspring_stiffness = 0.001\n", path.to_str(), || {
+        libscroll::set_config_location(ConfigLocation::Disabled);
+        libscroll::reload_config().unwrap();
+        libscroll::set_runtime_override("flips_until_idle", 6.0).unwrap();
+
+        let config = Scrollview::new().config().clone();
+        assert_eq!(config.TIMESTEP, Config::default().TIMESTEP);
+        assert_eq!(config.SPRING_STIFFNESS, Config::default().SPRING_STIFFNESS);
+        assert_eq!(libscroll::config_source("timestep"), Some(Layer::Default));
+        assert_eq!(libscroll::config_source("spring_stiffness"), Some(Layer::Default));
+
+        // runtime overrides still apply
+        assert_eq!(config.FLIPS_TO_IDLE, 6);
+        libscroll::clear_runtime_override("flips_until_idle");
+    });
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn source_sections_become_profiles() {
+    let config = Config::from_ini_str("[config]\n\
+        acceleration_exponent = 1.5\n\
+        [touchpad]\n\
+        acceleration_exponent = 1.8\n\
+        [mousewheel_precise]\n\
+        friction_coefficient = 0.0002\n\
+        [app.viewer.touchscreen]\n\
+        timestep = 0.2\n").unwrap();
+
+    assert_eq!(config.ACCELERATION_EXPONENT, 1.5);
+    assert_eq!(config.for_source(Source::Touchpad).ACCELERATION_EXPONENT, 1.8);
+    assert_eq!(config.for_source(Source::Touchscreen).ACCELERATION_EXPONENT, 1.5);
+    assert_eq!(config.for_source(Source::PreciseMousewheel).FRICTION_COEFFICIENT, 0.0002);
+    // application sections only apply through the global config
+    assert_eq!(config.for_source(Source::Touchscreen).TIMESTEP, Config::default().TIMESTEP);
+
+    let mut layers = LayeredConfig::new();
+    layers.layer_mut(Layer::System).set("acceleration_exponent", 1.5).unwrap();
+    layers.layer_mut(Layer::User).set_for_source(Source::Touchpad, "acceleration_exponent", 1.8).unwrap();
+    assert_eq!(layers.resolve().for_source(Source::Touchpad).ACCELERATION_EXPONENT, 1.8);
+    assert_eq!(layers.source_for(Source::Touchpad, "acceleration_exponent"), Some(Layer::User));
+    assert_eq!(layers.source_for(Source::Mousewheel, "acceleration_exponent"), Some(Layer::System));
+}
+
+#[test]
+fn higher_layers_take_precedence_over_profiles_below_them() {
+    let mut layers = LayeredConfig::new();
+    layers.layer_mut(Layer::System).set_for_source(Source::Touchpad, "acceleration_exponent", 1.8).unwrap();
+    layers.layer_mut(Layer::System).set_for_source(Source::Touchpad, "flips_until_idle", 5.0).unwrap();
+    layers.layer_mut(Layer::User).set("acceleration_exponent", 1.5).unwrap();
+    layers.layer_mut(Layer::Runtime).set("flips_until_idle", 8.0).unwrap();
+
+    let touchpad = layers.resolve().for_source(Source::Touchpad);
+    assert_eq!(touchpad.ACCELERATION_EXPONENT, 1.5);
+    assert_eq!(touchpad.FLIPS_TO_IDLE, 8);
+    assert_eq!(layers.source_for(Source::Touchpad, "acceleration_exponent"), Some(Layer::User));
+    assert_eq!(layers.source_for(Source::Touchpad, "flips_until_idle"), Some(Layer::Runtime));
+
+    // a profile in the same layer still wins over that layer's general value
+    layers.layer_mut(Layer::Runtime).set_for_source(Source::Touchpad, "flips_until_idle", 3.0).unwrap();
+    assert_eq!(layers.resolve().for_source(Source::Touchpad).FLIPS_TO_IDLE, 3);
+    assert_eq!(layers.resolve().for_source(Source::Mousewheel).FLIPS_TO_IDLE, 8);
+}
+
+/// Distance a fling released after a steady pan along y travels before coming to rest
+fn fling_distance(source: Source, config: Config) -> f64 {
+    let mut scrollview = Scrollview::with_config(config).unwrap();
+    scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(source);
+
+    scrollview.push_interrupt(Some(1)).unwrap();
+    for i in 1..=10 {
+        scrollview.push_pan(Axis::Vertical, 10.0, Some(i * 8)).unwrap();
+        if i % 2 == 0 {
+            scrollview.sample(i * 8 + 4);
+        }
+    }
+    scrollview.push_fling(Some(84)).unwrap();
+    let released = scrollview.sample(84).y;
+
+    scrollview.predicted_rest_position().y - released
+}
+
+#[test]
+fn scrollviews_use_the_profile_of_their_source() {
+    let mut config = Config::default();
+    config.profiles.entry(Source::Touchscreen).or_default().set("friction_coefficient", 0.0004).unwrap();
+
+    let plain = fling_distance(Source::Touchpad, config.clone());
+    let profiled = fling_distance(Source::Touchscreen, config.clone());
+    let unprofiled = fling_distance(Source::Touchscreen, Config::default());
+
+    assert!(plain > 0.0 && unprofiled > 0.0);
+    assert!(profiled < unprofiled / 2.0, "profile friction not applied: {} vs {}", profiled, unprofiled);
+}
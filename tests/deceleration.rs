@@ -0,0 +1,341 @@
+extern crate libscroll;
+
+mod common;
+
+use std::sync::Arc;
+
+use libscroll::{
+    Axis, Config, Constant, Deceleration, DecelerationModel, DiagnosticKind, Error, Exponential, PowerLaw, ScrollPhase, Scrollview, Source,
+    Spring,
+};
+
+fn models() -> Vec<(&'static str, Box<dyn Deceleration>)> {
+    vec![
+        ("power law", Box::new(PowerLaw { coefficient: 0.00073, exponent: 1.3 })),
+        ("exponential power law", Box::new(PowerLaw { coefficient: 0.002, exponent: 2.0 })),
+        ("exponential", Box::new(Exponential { rate: 0.998 })),
+        ("constant", Box::new(Constant { deceleration: 0.002 })),
+    ]
+}
+
+#[test]
+fn models_come_to_rest_where_their_distance_says() {
+    for (name, model) in models() {
+        for &velocity in &[-3.0, 0.5, 2.0] {
+            let distance = model.distance(velocity);
+            assert!(distance * velocity > 0.0, "{} coasts the wrong way", name);
+
+            let inverse = model.velocity_for_distance(distance);
+            assert!((inverse - velocity).abs() < 1e-9, "{}: {} px comes from {}, not {}", name, distance, inverse, velocity);
+
+            // step through the fling a ms at a time
+            let (mut travelled, mut current) = (0.0, velocity);
+            while current != 0.0 {
+                let next = model.velocity_after(current, 1.0);
+                travelled += (current + next) / 2.0;
+                current = next;
+            }
+            assert!((travelled - distance).abs() < distance.abs() * 0.01, "{} stepped {} instead of {}", name, travelled, distance);
+        }
+    }
+}
+
+#[test]
+fn models_do_not_depend_on_the_step_size() {
+    for (name, model) in models() {
+        let whole = model.velocity_after(2.0, 48.0);
+        let stepped = (0..48).fold(2.0, |velocity, _| model.velocity_after(velocity, 1.0));
+
+        assert!((whole - stepped).abs() < 1e-9, "{}: {} in one step but {} in many", name, whole, stepped);
+    }
+}
+
+#[test]
+fn displacement_adds_up_to_the_distance() {
+    for (name, model) in models() {
+        let velocity = 2.0;
+        let distance = model.distance(velocity);
+        assert!((model.displacement_after(velocity, 1e9) - distance).abs() < 1e-9, "{} never gets all the way", name);
+
+        // coasting 20ms and then 30ms more is the same as coasting 50ms
+        let split = model.displacement_after(velocity, 20.0) + model.displacement_after(model.velocity_after(velocity, 20.0), 30.0);
+        assert!((split - model.displacement_after(velocity, 50.0)).abs() < 1e-9, "{}: {} in two goes", name, split);
+
+        let halfway = model.time_to_travel(velocity, distance / 2.0).unwrap();
+        assert!((model.displacement_after(velocity, halfway) - distance / 2.0).abs() < 1e-6, "{} is not halfway after {}ms", name, halfway);
+        assert_eq!(model.time_to_travel(velocity, distance * 2.0), None);
+    }
+}
+
+#[test]
+fn config_chooses_the_model() {
+    let config = Config::from_ini_str("[config]\ndeceleration = exponential\ndeceleration_rate = 0.99\n").unwrap();
+    assert_eq!(config.DECELERATION, DecelerationModel::Exponential);
+    assert_eq!(config.deceleration().distance(2.0), Exponential { rate: 0.99 }.distance(2.0));
+
+    match Config::from_ini_str("[config]\ndeceleration = quadratic\n") {
+        Err(Error::InvalidConfig(diagnostics)) => {
+            assert!(matches!(diagnostics[0].kind, DiagnosticKind::TypeError { .. }), "{:?}", diagnostics);
+            assert!(diagnostics[0].to_string().contains("one of power_law, exponential, constant"), "{}", diagnostics[0]);
+        },
+        other => panic!("unknown model accepted: {:?}", other.map(|c| c.DECELERATION)),
+    }
+}
+
+#[test]
+fn friction_coefficient_keeps_its_units() {
+    // given against velocities in units of TIMESTEP, which are TIMESTEP / 2 px/ms, so the same
+    // value slows a fling down the same way in those units whatever the TIMESTEP
+    for &timestep in &[0.1, 0.2] {
+        let config = Config::from_ini_str(&format!("[config]\ntimestep = {}\nfriction_coefficient = 0.0002\n", timestep)).unwrap();
+        let in_steps = |px_per_ms: f64| px_per_ms * timestep / 2.0;
+
+        // a velocity of 1 loses the coefficient every ms, as 1^(exponent - 1) is 1
+        let after = in_steps(config.deceleration().velocity_after(1.0 / in_steps(1.0), 1.0));
+        assert!((after - (1.0 - 0.0002)).abs() < 1e-7, "slowed to {} in a ms with a timestep of {}", after, timestep);
+    }
+}
+
+/// Releases a steady pan along y, then samples every `frame` ms. Returns the release position
+/// and velocity and the positions at each multiple of 48ms after release
+fn fling(config: Config, frame: u64) -> (f64, f64, Vec<f64>) {
+    let mut scrollview = Scrollview::with_config(config).unwrap();
+    scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+
+    common::fling(&mut scrollview, &[10.0; 10]);
+    let released = scrollview.sample(84).y;
+    let velocity = scrollview.velocity(84).y;
+
+    let mut positions = Vec::new();
+    for time in (84 + frame..=84 + 960).step_by(frame as usize) {
+        let position = scrollview.sample(time).y;
+        if (time - 84) % 48 == 0 {
+            positions.push(position);
+        }
+    }
+
+    (released, velocity, positions)
+}
+
+#[test]
+fn flings_follow_the_configured_model_at_any_frame_rate() {
+    let config = Config { DECELERATION: DecelerationModel::Constant, CONSTANT_DECELERATION: 0.01, ..Config::default() };
+
+    let (released, velocity, slow) = fling(config.clone(), 16);
+    let (_, _, fast) = fling(config.clone(), 4);
+
+    let expected = Constant { deceleration: 0.01 }.distance(velocity);
+    let travelled = slow.last().unwrap() - released;
+    assert!((travelled - expected).abs() < 1.0, "travelled {} but the model says {}", travelled, expected);
+
+    for (slow, fast) in slow.iter().zip(&fast) {
+        assert!((slow - fast).abs() < 0.5, "16ms frames at {} but 4ms frames at {}", slow, fast);
+    }
+}
+
+#[test]
+fn scrollviews_can_be_given_their_own_model() {
+    let stopped: Arc<dyn Deceleration> = Arc::new(Constant { deceleration: 1000.0 });
+
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+    scrollview.set_deceleration(Some(stopped));
+
+    common::fling(&mut scrollview, &[10.0; 10]);
+    let released = scrollview.sample(84).y;
+
+    assert!((scrollview.predicted_rest_position().y - released).abs() < 0.1);
+}
+
+#[test]
+fn long_stalls_sample_straight_to_rest() {
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+
+    common::fling(&mut scrollview, &[10.0; 10]);
+    let predicted = scrollview.predicted_rest_position().y;
+
+    // stepping a fling through a stall this long would never finish
+    let first = scrollview.sample(84 + 1_000_000_000).y;
+    let rest = scrollview.sample(84 + 1_000_000_016).y;
+
+    assert!((first - predicted).abs() < 1e-6, "stopped at {} rather than {}", first, predicted);
+    assert_eq!(rest, predicted);
+    assert!(!scrollview.animating());
+}
+
+#[test]
+fn content_at_rest_samples_in_one_go() {
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+
+    let release = common::fling(&mut scrollview, &[10.0; 10]);
+    let mut time = release;
+    while scrollview.animating() {
+        time += 16;
+        scrollview.sample(time);
+    }
+    let rest = scrollview.sample(time).y;
+
+    // stepping through this long since the content came to rest would never finish
+    assert_eq!(scrollview.sample(time + 1_000_000_000).y, rest);
+    assert_eq!(scrollview.velocity(time + 1_000_000_000).y, 0.0);
+
+    // nor once a pan has been interrupted without a fling
+    let interrupted = time + 1_000_000_000;
+    scrollview.push_pan(Axis::Vertical, 20.0, Some(interrupted + 8)).unwrap();
+    scrollview.sample(interrupted + 12);
+    scrollview.push_interrupt(Some(interrupted + 16)).unwrap();
+    let held = scrollview.sample(interrupted + 16).y;
+    assert_eq!(scrollview.sample(interrupted + 2_000_000_000).y, held);
+}
+
+#[test]
+fn flings_come_to_rest_where_their_model_says() {
+    // MIN_VELOCITY_TO_IDLE in px/ms, where flings stop coasting
+    let config = Config::default();
+    let stop = config.MIN_VELOCITY_TO_IDLE * 2.0 / config.TIMESTEP;
+
+    for (name, model) in models() {
+        let model: Arc<dyn Deceleration> = Arc::from(model);
+
+        let mut scrollview = Scrollview::with_config(config.clone()).unwrap();
+        scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+        scrollview.set_source(Source::Touchscreen);
+        scrollview.set_deceleration(Some(model.clone()));
+
+        common::pan(&mut scrollview, &[10.0; 10]);
+        let release = scrollview.sample_state(84).y;
+        scrollview.push_fling(Some(84)).unwrap();
+        let predicted = scrollview.predicted_rest_position().y;
+
+        // worked out from the model alone, coasting until it slows to the idle velocity
+        let expected = release.position + model.distance(release.velocity) - model.distance(stop);
+        assert!((predicted - expected).abs() < 1e-6, "{} predicted {} rather than {}", name, predicted, expected);
+
+        // and the content gets there on its own, slowing all the way rather than jumping at the end
+        let (mut last, mut rest) = (release.position, None);
+        for time in (92..100_000).step_by(8) {
+            let position = scrollview.sample(time).y;
+            assert!(position >= last, "{} went backwards at {}ms", name, time);
+            assert!(position - last <= release.velocity * 8.0, "{} sped up at {}ms", name, time);
+
+            if !scrollview.animating() {
+                assert!(position - last <= stop * 8.0, "{} jumped {}px onto its rest", name, position - last);
+                rest = Some(position);
+                break;
+            }
+            last = position;
+        }
+
+        assert_eq!(rest, Some(predicted), "{} came to rest somewhere else", name);
+    }
+}
+
+#[test]
+fn bounces_are_sampled_the_same_at_any_frame_rate() {
+    let bounce = |frame: usize| {
+        let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+        scrollview.set_geometry(2000.0, 500.0, 500.0, 500.0).unwrap();
+        scrollview.set_source(Source::Touchscreen);
+
+        common::fling(&mut scrollview, &[-10.0; 10]);
+
+        let mut positions = Vec::new();
+        for time in (84 + frame as u64..=84 + 256).step_by(frame) {
+            let position = scrollview.sample(time).y;
+            if (time - 84) % 32 == 0 {
+                positions.push(position);
+            }
+        }
+
+        positions
+    };
+
+    let (slow, fast) = (bounce(32), bounce(8));
+    assert!(slow.iter().any(|&position| position < -1.0), "never overscrolled: {:?}", slow);
+
+    for (slow, fast) in slow.iter().zip(&fast) {
+        assert!((slow - fast).abs() < 1e-6, "32ms frames at {} but 8ms frames at {}", slow, fast);
+    }
+}
+
+/// Flings a touchscreen scrollview with `content` px along y, making `change` 100ms into the
+/// fling. Returns the furthest the content moved in a single 8ms frame before the change and
+/// from it on, along with the rest position predicted after the change and the one reached
+fn change_mid_fling(config: Config, content: f64, change: impl FnOnce(&mut Scrollview)) -> (f64, f64, f64, f64) {
+    let mut scrollview = Scrollview::with_config(config).unwrap();
+    scrollview.set_geometry(content, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+    let release = common::fling(&mut scrollview, &[40.0; 10]);
+
+    let (mut before, mut after) = (0.0f64, 0.0f64);
+    let mut last = scrollview.sample(release).y;
+    for frame in 1..=12 {
+        let position = scrollview.sample(release + frame * 8).y;
+        before = before.max((position - last).abs());
+        last = position;
+    }
+
+    change(&mut scrollview);
+    let predicted = scrollview.predicted_rest_position().y;
+
+    for frame in 13..2000 {
+        let position = scrollview.sample(release + frame * 8).y;
+        after = after.max((position - last).abs());
+        last = position;
+
+        if !scrollview.animating() {
+            return (before, after, predicted, position);
+        }
+    }
+
+    panic!("fling never came to rest after the change");
+}
+
+#[test]
+fn flings_carry_on_smoothly_when_the_physics_change() {
+    let mut profiled = Config::default();
+    profiled.profiles.entry(Source::Touchpad).or_default().set("friction_coefficient", 0.00002).unwrap();
+
+    type Change = Box<dyn FnOnce(&mut Scrollview)>;
+
+    let changes: Vec<(&str, Config, Change)> = vec![
+        ("exponential model", Config::default(), Box::new(|scrollview: &mut Scrollview| {
+            let config = Config { DECELERATION: DecelerationModel::Exponential, ..Config::default() };
+            scrollview.set_config(config).unwrap();
+        })),
+        ("longer timestep", Config::default(), Box::new(|scrollview: &mut Scrollview| {
+            scrollview.set_config(Config { TIMESTEP: 0.3, ..Config::default() }).unwrap();
+        })),
+        ("source with its own friction", profiled, Box::new(|scrollview: &mut Scrollview| {
+            scrollview.set_source(Source::Touchpad);
+        })),
+        ("own deceleration", Config::default(), Box::new(|scrollview: &mut Scrollview| {
+            scrollview.set_deceleration(Some(Arc::new(Constant { deceleration: 0.01 })));
+        })),
+    ];
+
+    for (name, config, change) in changes {
+        let (before, after, predicted, rest) = change_mid_fling(config, 100_000.0, change);
+
+        assert!(before > 0.0);
+        assert!(after <= before, "{}: moved {}px in a frame after the change, at most {}px before", name, after, before);
+        assert!((rest - predicted).abs() < 1e-6, "{}: came to rest at {} but predicted {}", name, rest, predicted);
+    }
+}
+
+#[test]
+fn bounces_carry_on_smoothly_when_the_spring_changes() {
+    let (before, after, predicted, rest) = change_mid_fling(Config::default(), 2000.0, |scrollview| {
+        assert_eq!(scrollview.phase(Axis::Vertical), ScrollPhase::Bouncing);
+        scrollview.set_spring(Some(Spring::with_response(150.0, 0.5).unwrap())).unwrap();
+    });
+
+    assert!(after <= before, "moved {}px in a frame after the change, at most {}px before", after, before);
+    assert_eq!((predicted, rest), (1500.0, 1500.0));
+}
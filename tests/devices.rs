@@ -0,0 +1,145 @@
+extern crate libscroll;
+
+mod common;
+
+use libscroll::{Capabilities, Config, DeviceId, DiagnosticKind, Error, Layer, LayeredConfig, Overrides, Scrollview, Source};
+
+#[test]
+fn device_sections_and_match_table_choose_profiles() {
+    let config = Config::from_ini_str("[config]\n\
+        friction_coefficient = 0.0001\n\
+        [device.trackpoint]\n\
+        friction_coefficient = 0.0003\n\
+        kinetic = no\n\
+        [devices]\n\
+        TrackPoint = device.trackpoint\n\
+        Wacom = touchscreen\n\
+        Synaptics = vendor_touchpad\n").unwrap();
+
+    let trackpoint = Source::Device(DeviceId::named("trackpoint"));
+    assert_eq!(config.for_source(trackpoint).FRICTION_COEFFICIENT, 0.0003);
+    assert_eq!(config.for_source(Source::Touchpad).FRICTION_COEFFICIENT, 0.0001);
+
+    assert_eq!(config.source_for_device("TPPS/2 IBM TrackPoint"), Some(trackpoint));
+    assert_eq!(config.source_for_device("Wacom Intuos Pro M Pen"), Some(Source::Touchscreen));
+    assert_eq!(config.source_for_device("synaptics tm3276-022"), Some(Source::Device(DeviceId::named("vendor_touchpad"))));
+    assert_eq!(config.source_for_device("Logitech USB Receiver"), None);
+
+    let mut scrollview = Scrollview::with_config(config).unwrap();
+    assert_eq!(scrollview.set_source_for_device("TPPS/2 IBM TrackPoint"), trackpoint);
+    assert_eq!(scrollview.capabilities(), Capabilities { overscrolls: false, kinetic: false, accelerates: false });
+    assert_eq!(scrollview.set_source_for_device("Logitech USB Receiver"), Source::Undefined);
+}
+
+#[test]
+fn switching_device_mid_fling_keeps_the_content_continuous() {
+    let config = Config::from_ini_str("[device.trackpoint]\n\
+        friction_coefficient = 0.00002\n\
+        kinetic = yes\n\
+        [devices]\n\
+        TrackPoint = device.trackpoint\n").unwrap();
+
+    let mut scrollview = Scrollview::with_config(config).unwrap();
+    scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+    let release = common::fling(&mut scrollview, &[40.0; 10]);
+
+    let (mut before, mut after) = (0.0f64, 0.0f64);
+    let mut last = scrollview.sample(release).y;
+    for frame in 1..=24 {
+        if frame == 13 {
+            scrollview.set_source_for_device("TPPS/2 IBM TrackPoint");
+        }
+
+        let position = scrollview.sample(release + frame * 8).y;
+        let delta = (position - last).abs();
+        if frame <= 12 { before = before.max(delta) } else { after = after.max(delta) }
+        last = position;
+    }
+
+    assert!(before > 0.0);
+    assert!(after <= before, "moved {}px in a frame after the switch, at most {}px before", after, before);
+    assert!(scrollview.animating());
+}
+
+#[test]
+fn malformed_device_entries_are_reported() {
+    assert!(Config::from_ini_str("[devices]\nWacom =\n").is_err());
+}
+
+#[test]
+fn device_entries_naming_missing_sections_are_reported() {
+    let diagnostics = match Config::from_ini_str("[device.trackpoint]\n\
+        kinetic = no\n\
+        [devices]\n\
+        TrackPoint = device.trackpiont\n\
+        Wacom = device.pen\n") {
+        Err(Error::InvalidConfig(diagnostics)) => diagnostics,
+        other => panic!("expected diagnostics, got {:?}", other),
+    };
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].line, Some(4));
+    assert_eq!(diagnostics[0].key, "TrackPoint");
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::UnknownProfile {
+        profile: "device.trackpiont".to_owned(),
+        suggestion: Some("device.trackpoint".to_owned()),
+    });
+    assert_eq!(diagnostics[1].kind, DiagnosticKind::UnknownProfile { profile: "device.pen".to_owned(), suggestion: None });
+
+    // the section may come after the table, and bare names are left for register_device()
+    assert!(Config::from_ini_str("[devices]\nTrackPoint = device.trackpoint\nPen = pen\n[device.trackpoint]\nkinetic = no\n").is_ok());
+}
+
+#[test]
+fn registered_devices_are_selected_with_set_source() {
+    let mut profile = Overrides::new();
+    profile.set("acceleration_exponent", 1.1).unwrap();
+
+    let mut config = Config::default();
+    let pen = config.register_device("pen", Capabilities { overscrolls: true, kinetic: false, accelerates: false }, &profile);
+    assert_eq!(pen, Source::Device(DeviceId::named("pen")));
+    assert_eq!(DeviceId::named("pen").name(), "pen");
+
+    let mut scrollview = Scrollview::with_config(config).unwrap();
+    scrollview.set_source(pen);
+    assert_eq!(scrollview.capabilities(), Capabilities { overscrolls: true, kinetic: false, accelerates: false });
+
+    // devices without a profile are treated like Source::Undefined
+    scrollview.set_source(Source::Device(DeviceId::named("unknown")));
+    assert_eq!(scrollview.capabilities(), Capabilities::of(Source::Undefined));
+}
+
+#[test]
+fn registering_a_device_again_extends_its_profile() {
+    let (mut first, mut second) = (Overrides::new(), Overrides::new());
+    first.set("acceleration_exponent", 1.1).unwrap();
+    first.set("flips_until_idle", 5.0).unwrap();
+    second.set("flips_until_idle", 8.0).unwrap();
+    let capabilities = Capabilities { overscrolls: true, kinetic: false, accelerates: false };
+
+    let mut config = Config::default();
+    config.register_device("stylus", capabilities, &first);
+    let stylus = config.register_device("stylus", capabilities, &second);
+
+    let mut overrides = Overrides::new();
+    overrides.register_device("stylus", capabilities, &first);
+    overrides.register_device("stylus", capabilities, &second);
+    let mut layers = LayeredConfig::new();
+    layers.set_layer(Layer::Runtime, overrides);
+
+    for config in &[config.for_source(stylus), layers.resolve().for_source(stylus)] {
+        assert_eq!(config.ACCELERATION_EXPONENT, 1.1);
+        assert_eq!(config.FLIPS_TO_IDLE, 8);
+        assert_eq!(config.KINETIC, Some(false));
+    }
+}
+
+#[test]
+fn higher_layers_match_devices_first() {
+    let mut layers = LayeredConfig::new();
+    layers.layer_mut(Layer::System).match_device("wacom", Source::Touchscreen);
+    layers.layer_mut(Layer::User).match_device("wacom", Source::Device(DeviceId::named("pen")));
+
+    assert_eq!(layers.resolve().source_for_device("Wacom Intuos"), Some(Source::Device(DeviceId::named("pen"))));
+}
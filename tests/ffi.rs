@@ -0,0 +1,159 @@
+extern crate libscroll;
+
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+use libscroll::ConfigLocation;
+
+type Handle = *mut c_void;
+
+// declared the way a C caller sees them in include/libscroll.h
+extern "C" {
+    fn rlscroll_create_scrollview() -> Handle;
+    fn rlscroll_destroy_scrollview(handle: Handle);
+    fn rlscroll_set_geometry(handle: Handle, content_height: f64, content_width: f64, viewport_height: f64, viewport_width: f64) -> c_int;
+    fn rlscroll_animating(handle: Handle) -> c_int;
+    fn rlscroll_set_source_touchscreen(handle: Handle) -> c_int;
+    fn rlscroll_set_source_for_device(handle: Handle, device: *const c_char) -> c_int;
+    fn rlscroll_signal_pan_y(handle: Handle, amount: f64, timestamp: u64) -> c_int;
+    fn rlscroll_signal_release(handle: Handle, timestamp: u64) -> c_int;
+    fn rlscroll_signal_interrupt(handle: Handle, timestamp: u64) -> c_int;
+    fn rlscroll_get_pan_y(handle: Handle, timestamp: u64) -> f64;
+    fn rlscroll_get_pos_x(handle: Handle, timestamp: u64) -> f64;
+    fn rlscroll_get_pos_y(handle: Handle, timestamp: u64) -> f64;
+    fn rlscroll_get_velocity_y(handle: Handle, timestamp: u64) -> f64;
+    fn rlscroll_get_phase_y(handle: Handle) -> c_int;
+    fn rlscroll_set_runtime_override(key: *const c_char, value: f64) -> c_int;
+    fn rlscroll_force_pan(handle: Handle, dx: f64, dy: f64) -> c_int;
+    fn rlscroll_force_jump(handle: Handle, x: f64, y: f64) -> c_int;
+}
+
+const RLSCROLL_OK: c_int = 0;
+const RLSCROLL_ERR_NULL_HANDLE: c_int = -1;
+const RLSCROLL_ERR_ZERO_TIMESTAMP: c_int = -3;
+const RLSCROLL_ERR_NON_FINITE: c_int = -4;
+const RLSCROLL_ERR_INVALID_CONFIG: c_int = -8;
+
+const RLSCROLL_PHASE_IDLE: c_int = 0;
+const RLSCROLL_PHASE_TRACKING: c_int = 1;
+const RLSCROLL_PHASE_FLINGING: c_int = 2;
+
+/// A touchscreen scrollview with 1500px to scroll through along y, using the built in defaults
+unsafe fn scrollview() -> Handle {
+    libscroll::set_config_location(ConfigLocation::Disabled);
+
+    let handle = rlscroll_create_scrollview();
+    assert!(!handle.is_null());
+    assert_eq!(rlscroll_set_geometry(handle, 2000.0, 500.0, 500.0, 500.0), RLSCROLL_OK);
+    assert_eq!(rlscroll_set_source_touchscreen(handle), RLSCROLL_OK);
+
+    handle
+}
+
+#[test]
+fn null_handles_are_refused() {
+    unsafe {
+        let null = ptr::null_mut();
+
+        assert_eq!(rlscroll_set_geometry(null, 1.0, 1.0, 1.0, 1.0), RLSCROLL_ERR_NULL_HANDLE);
+        assert_eq!(rlscroll_set_source_touchscreen(null), RLSCROLL_ERR_NULL_HANDLE);
+        assert_eq!(rlscroll_signal_pan_y(null, 1.0, 8), RLSCROLL_ERR_NULL_HANDLE);
+        assert_eq!(rlscroll_signal_release(null, 8), RLSCROLL_ERR_NULL_HANDLE);
+        assert_eq!(rlscroll_get_phase_y(null), RLSCROLL_ERR_NULL_HANDLE);
+        assert_eq!(rlscroll_force_pan(null, 1.0, 1.0), RLSCROLL_ERR_NULL_HANDLE);
+        assert_eq!(rlscroll_force_jump(null, 1.0, 1.0), RLSCROLL_ERR_NULL_HANDLE);
+        assert_eq!(rlscroll_set_source_for_device(null, ptr::null()), RLSCROLL_ERR_NULL_HANDLE);
+
+        // outputs have nothing to report
+        assert_eq!(rlscroll_animating(null), 0);
+        assert_eq!(rlscroll_get_pos_y(null, 8), 0.0);
+        assert_eq!(rlscroll_get_pan_y(null, 8), 0.0);
+
+        // destroying nothing is allowed, like free(NULL)
+        rlscroll_destroy_scrollview(null);
+    }
+}
+
+#[test]
+fn errors_map_to_status_codes() {
+    unsafe {
+        let handle = scrollview();
+
+        assert_eq!(rlscroll_signal_pan_y(handle, 1.0, 0), RLSCROLL_ERR_ZERO_TIMESTAMP);
+        assert_eq!(rlscroll_signal_pan_y(handle, f64::NAN, 8), RLSCROLL_ERR_NON_FINITE);
+        assert_eq!(rlscroll_set_geometry(handle, f64::INFINITY, 500.0, 500.0, 500.0), RLSCROLL_ERR_NON_FINITE);
+        assert_eq!(rlscroll_set_source_for_device(handle, ptr::null()), RLSCROLL_ERR_INVALID_CONFIG);
+        assert_eq!(rlscroll_set_runtime_override(ptr::null(), 1.0), RLSCROLL_ERR_INVALID_CONFIG);
+
+        let unknown = CString::new("no_such_key").unwrap();
+        assert_eq!(rlscroll_set_runtime_override(unknown.as_ptr(), 1.0), RLSCROLL_ERR_INVALID_CONFIG);
+
+        rlscroll_destroy_scrollview(handle);
+    }
+}
+
+#[test]
+fn gestures_drive_the_outputs() {
+    unsafe {
+        let handle = scrollview();
+        assert_eq!(rlscroll_get_phase_y(handle), RLSCROLL_PHASE_IDLE);
+        assert_eq!(rlscroll_animating(handle), 0);
+
+        assert_eq!(rlscroll_signal_interrupt(handle, 1), RLSCROLL_OK);
+        let mut panned = 0.0;
+        for i in 1..=10 {
+            assert_eq!(rlscroll_signal_pan_y(handle, 10.0, i * 8), RLSCROLL_OK);
+            panned += rlscroll_get_pan_y(handle, i * 8 + 4);
+        }
+
+        // pans add up to the position, and asking again in the same frame reports no more movement
+        assert!(panned > 0.0);
+        assert_eq!(rlscroll_get_pos_y(handle, 84), panned);
+        assert_eq!(rlscroll_get_pan_y(handle, 84), 0.0);
+        assert_eq!(rlscroll_get_phase_y(handle), RLSCROLL_PHASE_TRACKING);
+
+        assert_eq!(rlscroll_signal_release(handle, 84), RLSCROLL_OK);
+        assert!(rlscroll_get_velocity_y(handle, 100) > 0.0);
+        assert_eq!(rlscroll_get_phase_y(handle), RLSCROLL_PHASE_FLINGING);
+        assert_eq!(rlscroll_animating(handle), 1);
+        assert!(rlscroll_get_pos_y(handle, 100) > panned);
+
+        rlscroll_destroy_scrollview(handle);
+    }
+}
+
+#[test]
+fn manual_moves_stop_flings() {
+    unsafe {
+        let handle = scrollview();
+
+        assert_eq!(rlscroll_force_jump(handle, 0.0, 700.0), RLSCROLL_OK);
+        assert_eq!(rlscroll_get_pos_y(handle, 10), 700.0);
+        assert_eq!(rlscroll_get_phase_y(handle), RLSCROLL_PHASE_IDLE);
+
+        assert_eq!(rlscroll_signal_interrupt(handle, 20), RLSCROLL_OK);
+        for i in 1..=10 {
+            assert_eq!(rlscroll_signal_pan_y(handle, 10.0, 20 + i * 8), RLSCROLL_OK);
+            rlscroll_get_pos_y(handle, 24 + i * 8);
+        }
+        assert_eq!(rlscroll_signal_release(handle, 104), RLSCROLL_OK);
+        let flung = rlscroll_get_pos_y(handle, 120);
+        assert_eq!(rlscroll_animating(handle), 1);
+
+        assert_eq!(rlscroll_force_pan(handle, 0.0, 10.0), RLSCROLL_OK);
+        assert_eq!(rlscroll_animating(handle), 0);
+        assert_eq!(rlscroll_get_pos_y(handle, 120), flung + 10.0);
+        assert_eq!(rlscroll_get_pos_y(handle, 600), flung + 10.0);
+
+        // clamped to the track, and non-finite positions are refused without moving
+        assert_eq!(rlscroll_force_jump(handle, 0.0, 5000.0), RLSCROLL_OK);
+        assert_eq!(rlscroll_get_pos_y(handle, 610), 1500.0);
+        assert_eq!(rlscroll_force_jump(handle, f64::NAN, 0.0), RLSCROLL_ERR_NON_FINITE);
+        assert_eq!(rlscroll_force_pan(handle, 0.0, f64::INFINITY), RLSCROLL_ERR_NON_FINITE);
+        assert_eq!(rlscroll_get_pos_y(handle, 620), 1500.0);
+        assert_eq!(rlscroll_get_pos_x(handle, 620), 0.0);
+
+        rlscroll_destroy_scrollview(handle);
+    }
+}
@@ -0,0 +1,91 @@
+extern crate libscroll;
+
+mod common;
+
+use libscroll::{Axis, AxisVector, Config, Easing, Error, ScrollPhase, Scrollview, Source};
+
+/// A touchscreen scrollview with 1500px to scroll through along y and 500px along x
+fn scrollview() -> Scrollview {
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(2000.0, 1000.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+
+    scrollview
+}
+
+/// Flings the content down from the top, returning the time of release
+fn fling(scrollview: &mut Scrollview) -> u64 {
+    common::fling(scrollview, &[10.0; 10])
+}
+
+#[test]
+fn jumps_move_without_animating() {
+    let mut scrollview = scrollview();
+    scrollview.jump_to(AxisVector::new(200.0, 700.0)).unwrap();
+
+    assert!(!scrollview.animating());
+    assert_eq!(scrollview.phase(Axis::Vertical), ScrollPhase::Idle);
+    assert_eq!(scrollview.sample(10).x, 200.0);
+    assert_eq!(scrollview.sample(10).y, 700.0);
+    assert_eq!(scrollview.velocity(10).y, 0.0);
+
+    scrollview.force_pan(-50.0, 100.0).unwrap();
+    assert!(!scrollview.animating());
+    let moved = scrollview.sample(20);
+    assert_eq!((moved.x, moved.y), (150.0, 800.0));
+    assert_eq!(scrollview.sample(1000).y, 800.0);
+}
+
+#[test]
+fn jumps_cancel_flings_and_animations() {
+    let mut flung = scrollview();
+    let release = fling(&mut flung);
+    flung.sample(release + 16);
+    assert!(flung.animating());
+
+    flung.jump_to(AxisVector::new(0.0, 300.0)).unwrap();
+    assert!(!flung.animating());
+    assert_eq!(flung.sample(release + 32).y, 300.0);
+    assert_eq!(flung.sample(release + 500).y, 300.0);
+    assert_eq!(flung.predicted_rest_position().y, 300.0);
+
+    let mut flung = scrollview();
+    let release = fling(&mut flung);
+    let position = flung.sample(release + 16).y;
+    flung.force_pan(0.0, 10.0).unwrap();
+    assert!(!flung.animating());
+    assert_eq!(flung.sample(release + 500).y, position + 10.0);
+
+    let mut animated = scrollview();
+    animated.scroll_to(AxisVector::new(0.0, 1000.0), Some(10), 200.0, Easing::Linear).unwrap();
+    animated.sample(60);
+    animated.jump_to(AxisVector::new(0.0, 100.0)).unwrap();
+    assert!(!animated.animating());
+    assert_eq!(animated.sample(500).y, 100.0);
+}
+
+#[test]
+fn jumps_are_clamped_to_the_track() {
+    let mut scrollview = scrollview();
+
+    scrollview.jump_to(AxisVector::new(-100.0, 5000.0)).unwrap();
+    let clamped = scrollview.sample(10);
+    assert_eq!((clamped.x, clamped.y), (0.0, 1500.0));
+
+    scrollview.force_pan(1000.0, -2000.0).unwrap();
+    let clamped = scrollview.sample(20);
+    assert_eq!((clamped.x, clamped.y), (500.0, 0.0));
+}
+
+#[test]
+fn non_finite_jumps_are_rejected() {
+    let mut scrollview = scrollview();
+    scrollview.jump_to(AxisVector::new(100.0, 100.0)).unwrap();
+
+    assert!(matches!(scrollview.jump_to(AxisVector::new(f64::NAN, 0.0)), Err(Error::NonFinite(_))));
+    assert!(matches!(scrollview.force_pan(0.0, f64::INFINITY), Err(Error::NonFinite(_))));
+
+    // neither axis moved
+    let unmoved = scrollview.sample(10);
+    assert_eq!((unmoved.x, unmoved.y), (100.0, 100.0));
+}
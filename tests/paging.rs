@@ -0,0 +1,95 @@
+extern crate libscroll;
+
+mod common;
+
+use libscroll::{Axis, AxisVector, Config, Scrollview, SnapAlign, SnapMode, SnapPoints, Source};
+
+/// A paging touchscreen scrollview with ten 500px pages along y, starting at `from`
+fn scrollview(from: f64) -> Scrollview {
+    with_config(Config::default(), from)
+}
+
+fn with_config(config: Config, from: f64) -> Scrollview {
+    let mut scrollview = Scrollview::with_config(config).unwrap();
+    scrollview.set_geometry(5000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+    scrollview.set_paging(Axis::Vertical, true);
+    scrollview.jump_to(AxisVector::new(0.0, from)).unwrap();
+
+    scrollview
+}
+
+/// Pans by `delta` every 8ms for ten frames and releases, then samples until the content comes
+/// to rest, returning where it was released, where it was predicted to rest and where it did
+fn fling(scrollview: &mut Scrollview, delta: f64) -> (f64, f64, f64) {
+    let release = common::pan(scrollview, &[delta; 10]);
+    let released = scrollview.sample(release).y;
+    scrollview.push_fling(Some(release)).unwrap();
+    let predicted = scrollview.predicted_rest_position().y;
+
+    for time in (release..10_000).step_by(8) {
+        let position = scrollview.sample(time).y;
+        if !scrollview.animating() {
+            return (released, predicted, position);
+        }
+    }
+
+    panic!("fling never came to rest");
+}
+
+/// Checks that the fling rests where it was predicted to, at `expected`
+fn assert_rests_at(scrollview: &mut Scrollview, delta: f64, expected: f64) {
+    let (released, predicted, rest) = fling(scrollview, delta);
+
+    assert_eq!(rest, expected, "released at {} and came to rest at {}", released, rest);
+    assert_eq!(rest, predicted);
+}
+
+#[test]
+fn slow_flings_move_one_page() {
+    assert_rests_at(&mut scrollview(0.0), 4.0, 500.0);
+    assert_rests_at(&mut scrollview(0.0), 7.0, 500.0);
+    assert_rests_at(&mut scrollview(2000.0), -7.0, 1500.0);
+}
+
+#[test]
+fn pages_are_counted_from_the_release() {
+    // starts in the first page but is released in the second, so moves on to the third
+    let (released, _, rest) = fling(&mut scrollview(400.0), 7.0);
+    assert!(released > 500.0);
+    assert_eq!(rest, 1000.0);
+
+    // released in the second page heading back, so returns to its start
+    assert_rests_at(&mut scrollview(700.0), -2.0, 500.0);
+
+    // with no velocity at all it settles on the nearest page
+    assert_rests_at(&mut scrollview(700.0), 0.0, 500.0);
+    assert_rests_at(&mut scrollview(800.0), 0.0, 1000.0);
+}
+
+#[test]
+fn fast_flings_skip_pages() {
+    let (_, predicted, rest) = fling(&mut scrollview(0.0), 10.0);
+    assert!(rest > 1000.0, "only moved to {}", rest);
+    assert_eq!(rest % 500.0, 0.0);
+    assert_eq!(rest, predicted);
+
+    // the same fling is held to one page when it isn't above the skip velocity
+    let config = Config { PAGE_SKIP_VELOCITY: 1000.0, ..Config::default() };
+    assert_rests_at(&mut with_config(config, 0.0), 10.0, 500.0);
+}
+
+#[test]
+fn paging_takes_precedence_over_snap_points() {
+    let snapped = |paging: bool| {
+        let mut scrollview = scrollview(0.0);
+        let snap = SnapPoints::points(vec![1234.0], SnapMode::Mandatory, SnapAlign::Start);
+        scrollview.set_snap_points(Axis::Vertical, Some(snap));
+        scrollview.set_paging(Axis::Vertical, paging);
+
+        scrollview
+    };
+
+    assert_rests_at(&mut snapped(true), 4.0, 500.0);
+    assert_rests_at(&mut snapped(false), 4.0, 1234.0);
+}
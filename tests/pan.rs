@@ -0,0 +1,158 @@
+extern crate libscroll;
+
+mod common;
+
+use std::sync::Arc;
+
+use libscroll::{AveragedLinear, Axis, AxisVector, Config, Hermite, LeastSquares, Linear, PanEvent, PanInterpolation, Scrollview, Source};
+
+fn events(values: &[(f64, f64)]) -> Vec<PanEvent> {
+    values.iter().map(|&(time, value)| PanEvent { time, value }).collect()
+}
+
+fn strategies() -> Vec<(&'static str, Box<dyn PanInterpolation>)> {
+    vec![
+        ("linear", Box::new(Linear)),
+        ("averaged", Box::new(AveragedLinear)),
+        ("hermite", Box::new(Hermite)),
+        ("least squares", Box::new(LeastSquares::default())),
+    ]
+}
+
+#[test]
+fn every_strategy_follows_a_steady_gesture() {
+    let steady = events(&[(8.0, 16.0), (16.0, 32.0), (24.0, 48.0), (32.0, 64.0), (40.0, 80.0)]);
+
+    for (name, strategy) in strategies() {
+        for &time in &[4.0, 12.0, 20.0, 36.0, 48.0] {
+            let position = strategy.position_at(&steady, time);
+            assert!((position - 2.0 * time).abs() < 1e-9, "{} gives {} at {}", name, position, time);
+        }
+    }
+}
+
+#[test]
+fn strategies_differ_on_an_accelerating_gesture() {
+    // position grows as time squared, reported every 24ms
+    let accelerating = events(&[(24.0, 576.0), (48.0, 2304.0), (72.0, 5184.0), (96.0, 9216.0)]);
+    let time: f64 = 60.0;
+
+    let linear = Linear.position_at(&accelerating, time);
+    let hermite = Hermite.position_at(&accelerating, time);
+
+    assert_eq!(linear, (2304.0 + 5184.0) / 2.0);
+    assert!((hermite - time * time).abs() < (linear - time * time).abs(), "hermite {} should be closer than linear {}", hermite, linear);
+}
+
+#[test]
+fn least_squares_smooths_jittery_events() {
+    // a steady 2px/ms gesture with alternating error in the reported positions
+    let jittery = events(&[(8.0, 19.0), (16.0, 29.0), (24.0, 51.0), (32.0, 61.0), (40.0, 83.0)]);
+
+    let slope = |strategy: &dyn PanInterpolation| strategy.position_at(&jittery, 40.0) - strategy.position_at(&jittery, 39.0);
+
+    assert!((slope(&LeastSquares::default()) - 2.0).abs() < (slope(&Linear) - 2.0).abs());
+}
+
+/// Reports no movement at all, whatever the events say
+struct Frozen;
+
+impl PanInterpolation for Frozen {
+    fn position_at(&self, _events: &[PanEvent], _time: f64) -> f64 {
+        0.0
+    }
+}
+
+#[test]
+fn scrollviews_track_with_the_strategy_they_are_given() {
+    let frozen: Arc<dyn PanInterpolation> = Arc::new(Frozen);
+
+    let pan = |strategy: Option<Arc<dyn PanInterpolation>>| {
+        let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+        scrollview.set_geometry(2000.0, 500.0, 500.0, 500.0).unwrap();
+        scrollview.set_source(Source::Touchscreen);
+        if let Some(strategy) = strategy {
+            scrollview.set_pan_interpolation(strategy);
+        }
+
+        let end = common::pan(&mut scrollview, &[10.0; 10]);
+
+        scrollview.sample(end).y
+    };
+
+    assert!(pan(None) > 0.0);
+    assert_eq!(pan(Some(frozen.clone())), 0.0);
+    assert_eq!(pan(Some(frozen.clone())), 0.0);
+    assert_eq!(Arc::strong_count(&frozen), 1);
+}
+
+#[test]
+fn tracking_velocity_does_not_depend_on_where_samples_fall() {
+    // 3ms is a whole number of 0.3ms steps, 2ms leaves a short step at the end
+    let velocity_after = |elapsed: u64| {
+        let mut scrollview = Scrollview::with_config(Config { TIMESTEP: 0.3, ..Config::default() }).unwrap();
+        scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+        scrollview.set_source(Source::Touchscreen);
+
+        scrollview.push_interrupt(Some(1)).unwrap();
+        for i in 1..=10 {
+            scrollview.push_pan(Axis::Vertical, 10.0, Some(i * 8)).unwrap();
+            scrollview.sample(i * 8 + 1);
+        }
+
+        scrollview.velocity(81 + elapsed).y
+    };
+
+    let (whole, short) = (velocity_after(3), velocity_after(2));
+    assert!(whole > 0.0);
+    assert!((whole - short).abs() < whole * 1e-6, "{} px/ms after whole steps but {} after a short one", whole, short);
+}
+
+#[test]
+fn tracking_velocity_holds_steady_far_down_the_content() {
+    // adding up 0.1ms steps overshoots some sample times by a rounding error, which mustn't be
+    // left over as a step of its own: far enough down the content both ends of it interpolate
+    // to the same position, and the content would stop tracking the pan
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+    scrollview.jump_to(AxisVector::new(0.0, 10_000.0)).unwrap();
+
+    scrollview.push_interrupt(Some(1)).unwrap();
+    let velocities: Vec<f64> = (1..=30)
+        .map(|i| {
+            scrollview.push_pan(Axis::Vertical, 10.0, Some(i * 8)).unwrap();
+            scrollview.sample(i * 8 + 4);
+            scrollview.velocity(i * 8 + 4).y
+        })
+        .collect();
+
+    let steady = velocities[5];
+    assert!(steady > 0.0);
+    for (i, &velocity) in velocities.iter().enumerate().skip(5) {
+        assert!((velocity - steady).abs() < steady * 1e-6, "{} px/ms after pan {} but {} steadily", velocity, i + 1, steady);
+    }
+}
+
+#[test]
+fn velocity_changes_smoothly_between_sparse_events() {
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(100_000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+    scrollview.push_interrupt(Some(1)).unwrap();
+
+    // an accelerating gesture reported every 24ms, sampled three times between each pair of events
+    for i in 1..=8u64 {
+        let timestamp = i * 24;
+        scrollview.push_pan(Axis::Vertical, 2.0 * i as f64, Some(timestamp)).unwrap();
+
+        let velocities: Vec<f64> = [18, 12, 6].iter()
+            .map(|before| scrollview.sample_state(timestamp - before).y.velocity)
+            .collect();
+
+        if i > 3 {
+            assert!(velocities[0] < velocities[1] && velocities[1] < velocities[2],
+                "velocity should keep rising between events {} and {}: {:?}", i - 1, i, velocities);
+        }
+    }
+}
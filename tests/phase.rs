@@ -0,0 +1,33 @@
+extern crate libscroll;
+
+mod common;
+
+use common::pan_diagonal;
+use libscroll::{Axis, AxisVector, Config, Easing, ScrollPhase, Scrollview, Source};
+
+fn scrollview() -> Scrollview {
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(2000.0, 2000.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchpad);
+    scrollview
+}
+
+#[test]
+fn phase_follows_gestures_through_timeouts_and_animations() {
+    let mut scrollview = scrollview();
+    assert_eq!(scrollview.phase(Axis::Vertical), ScrollPhase::Idle);
+
+    let last = pan_diagonal(&mut scrollview, 0.0, 6.0, 10);
+    scrollview.sample(last + 4);
+    assert_eq!(scrollview.phase(Axis::Vertical), ScrollPhase::Tracking);
+
+    // a gesture that stops sending events without a fling times out
+    scrollview.sample(last + 1000);
+    assert_eq!(scrollview.phase(Axis::Vertical), ScrollPhase::Idle);
+
+    scrollview.scroll_to(AxisVector::new(0.0, 1000.0), Some(last + 1000), 200.0, Easing::Linear).unwrap();
+    scrollview.sample(last + 1100);
+    assert_eq!(scrollview.phase(Axis::Vertical), ScrollPhase::Animating);
+    scrollview.sample(last + 1300);
+    assert_eq!(scrollview.phase(Axis::Vertical), ScrollPhase::Idle);
+}
@@ -0,0 +1,84 @@
+extern crate libscroll;
+
+mod common;
+
+use libscroll::{Axis, Config, Scrollview, SnapAlign, SnapMode, SnapPoints, Source};
+
+/// A touchscreen scrollview 500px tall with `content` px of content along y
+fn scrollview(content: f64, snap: Option<SnapPoints>) -> Scrollview {
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(content, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+    scrollview.set_snap_points(Axis::Vertical, snap);
+
+    scrollview
+}
+
+/// Flings the content down from the top and samples it every frame until it comes to rest,
+/// returning the predicted and the actual rest position
+fn fling(scrollview: &mut Scrollview) -> (f64, f64) {
+    let release = common::fling(scrollview, &[10.0; 10]);
+    let predicted = scrollview.predicted_rest_position().y;
+
+    for time in (release..10_000).step_by(8) {
+        let position = scrollview.sample(time).y;
+        if !scrollview.animating() {
+            return (predicted, position);
+        }
+    }
+
+    panic!("fling never came to rest");
+}
+
+/// Checks that the fling rests where it was predicted to, at `expected`
+fn assert_rests_at(scrollview: &mut Scrollview, expected: f64) {
+    let (predicted, rest) = fling(scrollview);
+
+    assert!((rest - expected).abs() < 1e-6, "came to rest at {} instead of {}", rest, expected);
+    assert!((rest - predicted).abs() < 1e-6, "came to rest at {} but predicted {}", rest, predicted);
+}
+
+#[test]
+fn mandatory_snap_points_catch_every_fling() {
+    // without snapping this fling comes to rest at around 11700
+    let (_, natural) = fling(&mut scrollview(20_000.0, None));
+    assert!(natural > 11_600.0 && natural < 11_900.0, "fixture changed, natural rest is {}", natural);
+
+    let rows = SnapPoints::interval(1000.0, SnapMode::Mandatory, SnapAlign::Start);
+    assert_rests_at(&mut scrollview(20_000.0, Some(rows)), 12_000.0);
+
+    let items = SnapPoints::points(vec![3000.0, 11_000.0, 14_000.0], SnapMode::Mandatory, SnapAlign::Start);
+    assert_rests_at(&mut scrollview(20_000.0, Some(items)), 11_000.0);
+}
+
+#[test]
+fn proximity_snap_points_only_catch_nearby_flings() {
+    let (_, natural) = fling(&mut scrollview(20_000.0, None));
+
+    let far = SnapPoints::interval(1000.0, SnapMode::Proximity(100.0), SnapAlign::Start);
+    assert_rests_at(&mut scrollview(20_000.0, Some(far)), natural);
+
+    let near = SnapPoints::interval(1000.0, SnapMode::Proximity(400.0), SnapAlign::Start);
+    assert_rests_at(&mut scrollview(20_000.0, Some(near)), 12_000.0);
+}
+
+#[test]
+fn snap_points_line_up_with_the_viewport() {
+    // the 500px viewport puts a centered point 250px into it, and an end aligned one at its bottom
+    let center = SnapPoints::interval(1000.0, SnapMode::Mandatory, SnapAlign::Center);
+    assert_rests_at(&mut scrollview(20_000.0, Some(center)), 11_750.0);
+
+    let end = SnapPoints::interval(1000.0, SnapMode::Mandatory, SnapAlign::End);
+    assert_rests_at(&mut scrollview(20_000.0, Some(end)), 11_500.0);
+}
+
+#[test]
+fn snap_points_past_the_end_pull_to_the_edge() {
+    // the track ends at 4500, short of where the fling would naturally go
+    let beyond = SnapPoints::points(vec![1000.0, 8000.0], SnapMode::Mandatory, SnapAlign::Start);
+    let (_, rest) = fling(&mut scrollview(5000.0, Some(beyond)));
+    assert_eq!(rest, 4500.0);
+
+    let unreachable = SnapPoints::points(vec![50_000.0], SnapMode::Mandatory, SnapAlign::Start);
+    assert_rests_at(&mut scrollview(20_000.0, Some(unreachable)), 19_500.0);
+}
@@ -0,0 +1,172 @@
+extern crate libscroll;
+
+mod common;
+
+use libscroll::{Config, DiagnosticKind, Error, Layer, LayeredConfig, Scrollview, Source, Spring};
+
+/// A scrollview with 1500px to scroll through along y, panned by `deltas` every 8ms from the
+/// top and released at the end. Returns the scrollview and the time of release
+fn released(config: Config, deltas: &[f64]) -> (Scrollview, u64) {
+    let mut scrollview = Scrollview::with_config(config).unwrap();
+    scrollview.set_geometry(2000.0, 500.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchscreen);
+
+    let release = common::fling(&mut scrollview, deltas);
+
+    (scrollview, release)
+}
+
+/// Samples every `frame` ms until the fling is over, returning every position along the way
+fn positions(scrollview: &mut Scrollview, from: u64, frame: u64) -> Vec<f64> {
+    let mut positions = Vec::new();
+    for time in (from..from + 5000).step_by(frame as usize) {
+        positions.push(scrollview.sample(time).y);
+        if !scrollview.animating() {
+            return positions;
+        }
+    }
+
+    panic!("fling never settled, last position {:?}", positions.last());
+}
+
+#[test]
+fn critically_damped_bounces_never_pass_the_edge() {
+    // pulled past the top, and flung hard into the bottom
+    let (mut pulled, release) = released(Config::default(), &[-10.0; 10]);
+    let (mut flung, _) = released(Config::default(), &[40.0; 10]);
+
+    let pulled = positions(&mut pulled, release, 4);
+    assert!(pulled.iter().all(|&position| position <= 0.0), "came back past the top: {:?}", pulled);
+    assert_eq!(pulled.last(), Some(&0.0));
+
+    let flung = positions(&mut flung, release, 4);
+    let overscrolled = flung.iter().position(|&position| position > 1500.0).expect("never reached the bottom");
+    assert!(flung[overscrolled..].iter().all(|&position| position >= 1500.0), "came back past the bottom: {:?}", flung);
+    assert_eq!(flung.last(), Some(&1500.0));
+}
+
+#[test]
+fn content_thrown_back_from_overscroll_stops_on_the_edge() {
+    // pulled well past the top, then thrown back down towards it as fast as it can go
+    let (mut thrown, release) = released(Config::default(), &[-40.0, -40.0, -40.0, -40.0, 2.0]);
+    let state = thrown.sample_state(release).y;
+    assert!(state.position < 0.0 && state.velocity > 0.0, "was not thrown back from overscroll: {:?}", state);
+
+    let thrown = positions(&mut thrown, release, 4);
+    assert!(thrown.iter().all(|&position| position <= 0.0), "went through the edge: {:?}", thrown);
+    assert_eq!(thrown.last(), Some(&0.0));
+}
+
+#[test]
+fn underdamped_bounces_swing_back_past_the_edge() {
+    let wobbly = Config { SPRING_DAMPING_RATIO: 0.3, ..Config::default() };
+    let (mut scrollview, release) = released(wobbly, &[-10.0; 10]);
+
+    let positions = positions(&mut scrollview, release, 4);
+    assert!(positions.iter().any(|&position| position > 1.0), "never swung past the top: {:?}", positions);
+    assert_eq!(positions.last(), Some(&0.0));
+}
+
+#[test]
+fn bounces_settle_at_the_same_time_at_any_frame_rate() {
+    let settle_time = |frame: u64| {
+        let (mut scrollview, release) = released(Config::default(), &[-10.0; 10]);
+        (positions(&mut scrollview, release, frame).len() as u64 - 1) * frame
+    };
+
+    let (slow, fast) = (settle_time(16), settle_time(2));
+    assert!(slow.abs_diff(fast) <= 16, "settled after {}ms at 16ms frames but {}ms at 2ms frames", slow, fast);
+}
+
+#[test]
+fn springs_are_configured_by_stiffness_or_response() {
+    let config = Config::from_ini_str("[config]\nspring_stiffness = 0.002\nspring_damping_ratio = 0.8\n").unwrap();
+    assert_eq!(config.spring(), Spring { stiffness: 0.002, damping_ratio: 0.8 });
+    assert!(Config::from_ini_str("[config]\nspring_damping_ratio = 0\n").is_err());
+
+    let spring = Spring::with_response(250.0, 1.0).unwrap();
+    assert!((spring.frequency() - 2.0 * std::f64::consts::PI / 250.0).abs() < 1e-12);
+    assert!(!spring.overshoots() && Spring { damping_ratio: 0.5, ..spring }.overshoots());
+
+    // a stiffer spring set on the scrollview brings the content back sooner
+    let (mut own, release) = released(Config::default(), &[-10.0; 10]);
+    own.set_spring(Some(Spring::with_response(100.0, 1.0).unwrap())).unwrap();
+    let (mut configured, _) = released(Config::default(), &[-10.0; 10]);
+
+    assert!(positions(&mut own, release, 4).len() < positions(&mut configured, release, 4).len());
+}
+
+#[test]
+fn springs_that_never_settle_are_refused() {
+    let keys = |result: Result<(), Error>| match result {
+        Err(Error::InvalidConfig(diagnostics)) => diagnostics.into_iter().map(|diagnostic| {
+            assert!(matches!(diagnostic.kind, DiagnosticKind::OutOfRange { .. }));
+            diagnostic.key
+        }).collect::<Vec<_>>(),
+        other => panic!("expected diagnostics, got {:?}", other),
+    };
+
+    let (mut scrollview, release) = released(Config::default(), &[-10.0; 10]);
+    assert_eq!(keys(scrollview.set_spring(Some(Spring { stiffness: 0.0, damping_ratio: 1.0 }))), ["spring_stiffness"]);
+    assert_eq!(keys(scrollview.set_spring(Some(Spring { stiffness: 0.001, damping_ratio: 0.0 }))), ["spring_damping_ratio"]);
+    assert_eq!(keys(scrollview.set_spring(Some(Spring { stiffness: f64::INFINITY, damping_ratio: -1.0 }))),
+        ["spring_stiffness", "spring_damping_ratio"]);
+
+    assert_eq!(keys(Spring::with_response(0.0, 1.0).map(drop)), ["response"]);
+    assert_eq!(keys(Spring::with_response(-250.0, 1.0).map(drop)), ["response"]);
+    assert_eq!(keys(Spring::with_response(250.0, f64::NAN).map(drop)), ["spring_damping_ratio"]);
+
+    // the configured spring is kept, and still brings the content back
+    let mut configured = released(Config::default(), &[-10.0; 10]).0;
+    assert_eq!(positions(&mut scrollview, release, 4), positions(&mut configured, release, 4));
+    assert!(!scrollview.animating());
+}
+
+#[test]
+fn deprecated_spring_keys_still_configure_the_spring() {
+    // as older versions stepped through it, with the default TIMESTEP of 0.1
+    let config = Config::from_ini_str("[config]\n\
+        content_mass_value = 3000\n\
+        overscroll_spring_constant = 0.6\n\
+        bounce_damping_factor = 0.995\n\
+        flips_until_idle = 5\n").unwrap();
+    let stiffness: f64 = 2.0 * 0.6 / (3000.0 * 0.1);
+    let damping_ratio = -0.995f64.ln() / 0.1 / (2.0 * stiffness.sqrt());
+
+    let spring = config.spring();
+    assert!((spring.stiffness - stiffness).abs() < 1e-12, "stiffness {} instead of {}", spring.stiffness, stiffness);
+    assert!((spring.damping_ratio - damping_ratio).abs() < 1e-9, "damping ratio {} instead of {}", spring.damping_ratio, damping_ratio);
+    assert_eq!(config.FLIPS_TO_IDLE, 5);
+
+    // the ones left out keep their old defaults
+    let damped = Config::from_ini_str("[config]\nbounce_damping_factor = 0.995\n").unwrap().spring();
+    assert_eq!(damped.stiffness, Config::default().SPRING_STIFFNESS);
+    let massive = Config::from_ini_str("[config]\ncontent_mass_value = 3000\n").unwrap().spring();
+    assert!((massive.stiffness - 2.0 * 0.4 / (3000.0 * 0.1)).abs() < 1e-12);
+    assert_eq!(massive.damping_ratio, Config::default().SPRING_DAMPING_RATIO);
+
+    assert!(Config::from_ini_str("[config]\nbounce_damping_factor = 1\n").is_err());
+}
+
+#[test]
+fn new_spring_keys_win_over_deprecated_ones() {
+    let both = Config::from_ini_str("[config]\n\
+        spring_stiffness = 0.002\n\
+        content_mass_value = 3000\n\
+        spring_damping_ratio = 0.8\n\
+        bounce_damping_factor = 0.995\n").unwrap();
+    assert_eq!(both.spring(), Spring { stiffness: 0.002, damping_ratio: 0.8 });
+
+    let mut layers = LayeredConfig::new();
+    layers.layer_mut(Layer::System).set("overscroll_spring_constant", 0.6).unwrap();
+    layers.layer_mut(Layer::System).set_for_source(Source::Touchpad, "bounce_damping_factor", 0.995).unwrap();
+    layers.layer_mut(Layer::User).set("spring_damping_ratio", 0.8).unwrap();
+    layers.layer_mut(Layer::Runtime).set("spring_stiffness", 0.002).unwrap();
+
+    assert_eq!(layers.resolve().spring(), Spring { stiffness: 0.002, damping_ratio: 0.8 });
+    assert_eq!(layers.resolve().for_source(Source::Touchpad).spring(), Spring { stiffness: 0.002, damping_ratio: 0.8 });
+
+    // while a deprecated key in a higher layer still replaces a new one below it
+    layers.layer_mut(Layer::Runtime).set("bounce_damping_factor", 0.995).unwrap();
+    assert!(layers.resolve().spring().damping_ratio < 0.8);
+}
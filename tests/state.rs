@@ -0,0 +1,36 @@
+extern crate libscroll;
+
+mod common;
+
+use common::{pan_diagonal, settle, FRAME_MS};
+use libscroll::{Config, ScrollPhase, Scrollview, Source};
+
+fn scrollview() -> Scrollview {
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(2000.0, 2000.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchpad);
+    scrollview
+}
+
+#[test]
+fn sampled_state_reports_velocity_overscroll_and_phase() {
+    let mut scrollview = scrollview();
+    let last = pan_diagonal(&mut scrollview, -40.0, 6.0, 40);
+
+    let state = scrollview.sample_state(last + 4);
+    assert!(state.x.overscroll < 0.0 && state.x.overscroll == state.x.position, "x should be past the start: {:?}", state.x);
+    assert_eq!(state.y.overscroll, 0.0);
+    assert!(state.y.velocity > 0.0, "y should be moving forward: {:?}", state.y);
+    assert_eq!((state.x.phase, state.y.phase), (ScrollPhase::Tracking, ScrollPhase::Tracking));
+
+    scrollview.push_fling(Some(last + 4)).unwrap();
+    let state = scrollview.sample_state(last + 4 + FRAME_MS);
+    assert_eq!((state.x.phase, state.y.phase), (ScrollPhase::Bouncing, ScrollPhase::Flinging));
+    assert!(state.y.velocity > 0.0, "y should coast forward: {:?}", state.y);
+
+    let rest = settle(&mut scrollview, last + 4 + 2 * FRAME_MS);
+    let state = scrollview.sample_state(last + 4 + 2000 * FRAME_MS);
+    assert_eq!((state.x.position, state.y.position), (rest.x, rest.y));
+    assert_eq!((state.y.velocity, state.y.overscroll), (0.0, 0.0));
+    assert_eq!((state.x.phase, state.y.phase), (ScrollPhase::Idle, ScrollPhase::Idle));
+}
@@ -0,0 +1,86 @@
+extern crate libscroll;
+
+mod common;
+
+use common::{pan_diagonal, settle, FRAME_MS};
+use libscroll::{Axis, Config, Error, Scrollview, Source};
+
+fn scrollview() -> Scrollview {
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(2000.0, 2000.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchpad);
+    scrollview
+}
+
+#[test]
+fn diagonal_pan_tracks_both_axes() {
+    let mut scrollview = scrollview();
+    let last = pan_diagonal(&mut scrollview, 4.0, 6.0, 40);
+
+    let position = scrollview.sample(last + 4);
+
+    assert!(position.x > 0.0, "x did not move: {}", position);
+    assert!(position.y > 0.0, "y did not move: {}", position);
+    assert!(position.y > position.x, "larger y deltas should move y further: {}", position);
+}
+
+#[test]
+fn horizontal_pan_leaves_vertical_untouched() {
+    let mut scrollview = scrollview();
+    let last = pan_diagonal(&mut scrollview, 5.0, 0.0, 40);
+
+    let position = scrollview.sample(last + 4);
+
+    assert!(position.x > 0.0, "x did not move: {}", position);
+    assert_eq!(position.y, 0.0);
+}
+
+#[test]
+fn diagonal_fling_coasts_on_both_axes() {
+    let mut scrollview = scrollview();
+    let last = pan_diagonal(&mut scrollview, 4.0, 6.0, 40);
+
+    let released = scrollview.sample(last + 4);
+    scrollview.push_fling(Some(last + 4)).unwrap();
+    assert!(scrollview.animating());
+
+    let predicted = scrollview.predicted_rest_position();
+    let rest = settle(&mut scrollview, last + 4 + FRAME_MS);
+
+    assert!(rest.x > released.x, "x did not coast: {} -> {}", released, rest);
+    assert!(rest.y > released.y, "y did not coast: {} -> {}", released, rest);
+    assert_eq!((rest.x, rest.y), (predicted.x, predicted.y), "fling did not land where predicted");
+}
+
+#[test]
+fn diagonal_overscroll_bounces_back_on_both_axes() {
+    let mut scrollview = scrollview();
+    let last = pan_diagonal(&mut scrollview, -40.0, -60.0, 40);
+
+    let released = scrollview.sample(last + 4);
+    assert!(released.x < 0.0, "x did not overscroll: {}", released);
+    assert!(released.y < 0.0, "y did not overscroll: {}", released);
+
+    scrollview.push_fling(Some(last + 4)).unwrap();
+    let predicted = scrollview.predicted_rest_position();
+    assert_eq!((predicted.x, predicted.y), (0.0, 0.0), "overscrolled fling should be predicted to rest on the edge");
+
+    let rest = settle(&mut scrollview, last + 4 + FRAME_MS);
+
+    assert!(rest.x.abs() < 1.0, "x did not return to the edge: {}", rest);
+    assert!(rest.y.abs() < 1.0, "y did not return to the edge: {}", rest);
+}
+
+#[test]
+fn bad_input_is_rejected() {
+    let mut scrollview = scrollview();
+
+    assert_eq!(scrollview.push_pan(Axis::Horizontal, 1.0, None), Err(Error::MissingTimestamp));
+    assert_eq!(scrollview.push_pan(Axis::Vertical, 1.0, Some(0)), Err(Error::ZeroTimestamp));
+    assert!(scrollview.push_pan(Axis::Vertical, f64::NAN, Some(8)).is_err());
+    assert!(scrollview.push_fling(None).is_err());
+    assert!(scrollview.set_geometry(f64::INFINITY, 100.0, 10.0, 10.0).is_err());
+
+    let position = scrollview.sample(16);
+    assert!(position.x.is_finite() && position.y.is_finite());
+}
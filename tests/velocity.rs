@@ -0,0 +1,34 @@
+extern crate libscroll;
+
+mod common;
+
+use common::{pan_diagonal, settle, FRAME_MS};
+use libscroll::{Config, Scrollview, Source};
+
+fn scrollview() -> Scrollview {
+    let mut scrollview = Scrollview::with_config(Config::default()).unwrap();
+    scrollview.set_geometry(2000.0, 2000.0, 500.0, 500.0).unwrap();
+    scrollview.set_source(Source::Touchpad);
+    scrollview
+}
+
+#[test]
+fn velocity_follows_the_fling_down_to_rest() {
+    let mut scrollview = scrollview();
+    let last = pan_diagonal(&mut scrollview, 0.0, 6.0, 40);
+    scrollview.push_fling(Some(last + 4)).unwrap();
+
+    let early = scrollview.velocity(last + 4 + FRAME_MS);
+    let position = scrollview.sample(last + 4 + FRAME_MS);
+    assert_eq!(scrollview.velocity(last + 4 + FRAME_MS).y, early.y, "asking again should not advance the fling");
+    assert_eq!(scrollview.sample(last + 4 + FRAME_MS).y, position.y);
+
+    let later = scrollview.velocity(last + 4 + 10 * FRAME_MS);
+    let moved = scrollview.sample(last + 4 + 11 * FRAME_MS).y - scrollview.sample(last + 4 + 10 * FRAME_MS).y;
+    assert_eq!(early.x, 0.0);
+    assert!(early.y > later.y && later.y > 0.0, "velocity should decay: {} -> {}", early.y, later.y);
+    assert!((moved / FRAME_MS as f64 - later.y).abs() < later.y * 0.1, "velocity {} px/ms but moved {} in a frame", later.y, moved);
+
+    settle(&mut scrollview, last + 4 + 12 * FRAME_MS);
+    assert_eq!(scrollview.velocity(last + 4 + 2000 * FRAME_MS).y, 0.0);
+}